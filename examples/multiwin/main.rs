@@ -0,0 +1,124 @@
+//! Example how to use `egui_multiwin` to run a root window that can open popup windows.
+pub mod windows;
+
+use std::{thread, time::Duration};
+
+use egui_multiwin::multi_window::{ExitPolicy, MultiWindow};
+use tracing_subscriber::EnvFilter;
+
+use windows::{
+    popup_window,
+    root::{self},
+    MyWindows,
+};
+
+/// Events sent to the event loop from outside of it.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// Sent once a second by a background thread.
+    Tick,
+}
+
+/// Messages the windows send each other.
+#[derive(Clone, Debug)]
+pub enum AppMessage {
+    /// Sent by the root window to every popup to clear its input.
+    ResetInput,
+    /// Sent by a popup to its root window when Enter is pressed in its input.
+    Submitted(String),
+}
+
+/// State shared by every window.
+#[derive(Default)]
+pub struct AppState {
+    /// Incremented by the popups and shown by the root window.
+    pub button_press_count: u32,
+    /// Shown by every window, but uploaded only once because the windows share their GL context.
+    pub logo: Option<egui::TextureId>,
+}
+
+fn main() {
+    // Logs lifecycle events by default; `RUST_LOG=egui_multiwin=trace` also shows every event.
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let mut multi_window: MultiWindow<MyWindows, AppEvent, AppState, AppMessage> =
+        MultiWindow::new();
+    match multi_window.share_gl_context() {
+        Ok(()) => {
+            let logo = multi_window
+                .shared_textures()
+                .map(|textures| textures.load(logo_image(), egui::TextureFilter::Linear));
+            multi_window.state_mut().logo = logo;
+        }
+        Err(e) => tracing::warn!(error = %e, "couldn't share the GL context between windows"),
+    }
+    // Windows reopen where they were left in the last run.
+    let geometry_file = std::env::temp_dir().join("egui-multiwin-example-geometry.ron");
+    if let Err(e) = multi_window.remember_geometry(geometry_file) {
+        tracing::warn!(error = %e, "couldn't load the window geometry");
+    }
+    // So does what egui remembers, such as the width of the root window's side panel.
+    #[cfg(feature = "persistence")]
+    {
+        let memory_file = std::env::temp_dir().join("egui-multiwin-example-memory.ron");
+        if let Err(e) = multi_window.remember_egui_memory(memory_file) {
+            tracing::warn!(error = %e, "couldn't load the egui memory");
+        }
+    }
+    // The windows that were open when the last run ended are reopened, the first run starts with
+    // a root window and one popup.
+    let session_file = std::env::temp_dir().join("egui-multiwin-example-session.ron");
+    let reopened = multi_window
+        .restore_session(&session_file)
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "couldn't restore the last session");
+            vec![]
+        });
+    multi_window.remember_session(session_file);
+    let reopened_root = reopened
+        .into_iter()
+        .find(|handle| matches!(multi_window.get(*handle), Some(MyWindows::Root(_))));
+    let root_handle = match reopened_root {
+        Some(root_handle) => root_handle,
+        None => {
+            let root_window = root::RootWindow::request();
+            let root_handle = multi_window
+                .add(root_window)
+                .expect("couldn't create the root window");
+            let root_window2 =
+                popup_window::PopupWindow::request("initial popup".to_string(), root_handle);
+            multi_window
+                .add(root_window2)
+                .expect("couldn't create the initial popup");
+            root_handle
+        }
+    };
+    multi_window.set_exit_policy(ExitPolicy::PrimaryWindowClosed(root_handle));
+
+    let proxy = multi_window.create_proxy();
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(1));
+        if proxy.send_event(AppEvent::Tick).is_err() {
+            // The event loop has exited.
+            break;
+        }
+    });
+
+    MultiWindow::run(multi_window);
+}
+
+/// A small gradient standing in for an icon loaded from a file.
+fn logo_image() -> egui::ColorImage {
+    let size = 32;
+    let pixels = (0..size * size)
+        .map(|i| egui::Color32::from_rgb((i % size * 8) as u8, (i / size * 8) as u8, 160))
+        .collect();
+    egui::ColorImage {
+        size: [size, size],
+        pixels,
+    }
+}
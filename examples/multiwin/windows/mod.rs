@@ -0,0 +1,147 @@
+use egui_multiwin::app_ctx::AppCtx;
+use egui_multiwin::egui_glow::EguiGlow;
+use egui_multiwin::message::Message;
+use egui_multiwin::multi_window::NewWindowRequest;
+use egui_multiwin::persistence::SessionState;
+use egui_multiwin::tracked_window::{
+    CloseRequestResponse, DisplayCreationError, TrackedWindow, TrackedWindowControl, WindowError,
+    WindowHandle,
+};
+use glutin::PossiblyCurrent;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppEvent, AppMessage, AppState};
+
+pub mod popup_window;
+pub mod root;
+
+pub enum MyWindows {
+    Root(root::RootWindow),
+    Popup(popup_window::PopupWindow),
+}
+
+impl From<root::RootWindow> for MyWindows {
+    fn from(window: root::RootWindow) -> Self {
+        MyWindows::Root(window)
+    }
+}
+
+impl From<popup_window::PopupWindow> for MyWindows {
+    fn from(window: popup_window::PopupWindow) -> Self {
+        MyWindows::Popup(window)
+    }
+}
+
+/// What is saved about each window at exit, to reopen it on the next launch.
+#[derive(Serialize, Deserialize)]
+pub enum SavedWindow {
+    Root { num_popups_created: u32 },
+    Popup { label: String, input: String },
+}
+
+impl SessionState for MyWindows {
+    type Saved = SavedWindow;
+
+    fn save_session(&self) -> Option<SavedWindow> {
+        Some(match self {
+            MyWindows::Root(root) => SavedWindow::Root {
+                num_popups_created: root.num_popups_created,
+            },
+            MyWindows::Popup(popup) => SavedWindow::Popup {
+                label: popup.label.clone(),
+                input: popup.input.clone(),
+            },
+        })
+    }
+
+    fn restore_session(
+        saved: SavedWindow,
+        parent: Option<WindowHandle>,
+    ) -> Option<NewWindowRequest<MyWindows>> {
+        match saved {
+            SavedWindow::Root { num_popups_created } => {
+                Some(root::RootWindow::restore(num_popups_created))
+            }
+            // Popups are owned by the root window, and aren't reopened without it.
+            SavedWindow::Popup { label, input } => {
+                parent.map(|root| popup_window::PopupWindow::restore(label, input, root))
+            }
+        }
+    }
+}
+
+impl TrackedWindow<MyWindows, AppEvent, AppState, AppMessage> for MyWindows {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        match self {
+            MyWindows::Root(w) => w.update(ctx, app),
+            MyWindows::Popup(w) => w.update(ctx, app),
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &glutin::event::Event<AppEvent>,
+        app: &mut AppCtx<MyWindows, AppState>,
+        egui: &mut EguiGlow,
+        gl_window: &mut glutin::WindowedContext<PossiblyCurrent>,
+    ) -> Result<Option<TrackedWindowControl<MyWindows, AppMessage>>, WindowError> {
+        match self {
+            MyWindows::Root(w) => w.handle_event(event, app, egui, gl_window),
+            MyWindows::Popup(w) => w.handle_event(event, app, egui, gl_window),
+        }
+    }
+
+    fn on_user_event(
+        &mut self,
+        event: &AppEvent,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        match self {
+            MyWindows::Root(w) => w.on_user_event(event, app),
+            MyWindows::Popup(w) => w.on_user_event(event, app),
+        }
+    }
+
+    fn on_window_created(
+        &mut self,
+        result: Result<WindowHandle, DisplayCreationError>,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        match self {
+            MyWindows::Root(w) => w.on_window_created(result, app),
+            MyWindows::Popup(w) => w.on_window_created(result, app),
+        }
+    }
+
+    fn on_parent_closed(&mut self, parent: WindowHandle) {
+        match self {
+            MyWindows::Root(w) => w.on_parent_closed(parent),
+            MyWindows::Popup(w) => w.on_parent_closed(parent),
+        }
+    }
+
+    fn on_close_requested(
+        &mut self,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> CloseRequestResponse {
+        match self {
+            MyWindows::Root(w) => w.on_close_requested(app),
+            MyWindows::Popup(w) => w.on_close_requested(app),
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: Message<AppMessage>,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        match self {
+            MyWindows::Root(w) => w.on_message(message, app),
+            MyWindows::Popup(w) => w.on_message(message, app),
+        }
+    }
+}
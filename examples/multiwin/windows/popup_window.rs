@@ -0,0 +1,113 @@
+use egui_multiwin::app_ctx::AppCtx;
+use egui_multiwin::message::{Message, Recipient};
+use egui_multiwin::{
+    multi_window::NewWindowRequest,
+    tracked_window::{TrackedWindow, TrackedWindowControl, WindowHandle},
+};
+use glutin::event_loop::ControlFlow;
+
+use crate::windows::MyWindows;
+use crate::{AppEvent, AppMessage, AppState};
+
+pub struct PopupWindow {
+    /// The title of the popup, which also tells it apart from the others across runs.
+    pub label: String,
+    pub input: String,
+    /// The root window that opened this popup.
+    parent: WindowHandle,
+}
+
+impl PopupWindow {
+    /// The popup is owned by `root`, so it is closed along with it.
+    pub fn request(label: String, root: WindowHandle) -> NewWindowRequest<MyWindows> {
+        let key = format!("popup/{}", label);
+        NewWindowRequest::new(
+            PopupWindow {
+                label: label.clone(),
+                input: label.clone(),
+                parent: root,
+            }
+            .into(),
+            glutin::window::WindowBuilder::new()
+                .with_resizable(false)
+                .with_inner_size(glutin::dpi::LogicalSize {
+                    width: 400.0,
+                    height: 200.0,
+                })
+                .with_title(label),
+        )
+        .with_parent(root)
+        .with_key(key)
+    }
+
+    /// Reopens a popup of an earlier run with the text that was typed into it.
+    pub fn restore(
+        label: String,
+        input: String,
+        root: WindowHandle,
+    ) -> NewWindowRequest<MyWindows> {
+        let mut request = Self::request(label, root);
+        if let MyWindows::Popup(popup) = &mut request.window_state {
+            popup.input = input;
+        }
+        request
+    }
+}
+
+impl TrackedWindow<MyWindows, AppEvent, AppState, AppMessage> for PopupWindow {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        let mut quit = false;
+        let mut messages = vec![];
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(logo) = app.state().logo {
+                ui.image(logo, [32.0, 32.0]);
+            }
+            if ui.button("Increment").clicked() {
+                app.state_mut().button_press_count += 1;
+            }
+            let response = ui.add(egui::TextEdit::singleline(&mut self.input));
+            if response.changed() {
+                // …
+            }
+            if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                messages.push((
+                    Recipient::Window(self.parent),
+                    AppMessage::Submitted(self.input.clone()),
+                ));
+            }
+            if ui.button("Quit").clicked() {
+                quit = true;
+            }
+        });
+
+        TrackedWindowControl {
+            requested_control_flow: if quit {
+                ControlFlow::Exit
+            } else {
+                ControlFlow::Wait
+            },
+            messages,
+            ..Default::default()
+        }
+    }
+
+    fn on_message(
+        &mut self,
+        message: Message<AppMessage>,
+        _app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        if let AppMessage::ResetInput = message.payload {
+            self.input.clear();
+        }
+        TrackedWindowControl::default()
+    }
+
+    fn on_parent_closed(&mut self, _parent: WindowHandle) {
+        tracing::info!(input = %self.input, "root window is gone, closing popup");
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::windows::popup_window::PopupWindow;
+use egui_multiwin::app_ctx::AppCtx;
+use egui_multiwin::message::{Message, Recipient};
+use egui_multiwin::{
+    multi_window::NewWindowRequest,
+    tracked_window::{
+        CloseRequestResponse, DisplayCreationError, TrackedWindow, TrackedWindowControl,
+        WindowHandle,
+    },
+};
+
+use crate::windows::MyWindows;
+use crate::{AppEvent, AppMessage, AppState};
+
+pub struct RootWindow {
+    pub num_popups_created: u32,
+    pub seconds_running: u32,
+    /// Set while asking the user whether closing the root (and its popups) is ok.
+    confirming_close: bool,
+    /// Filled in by a background thread once its job is done.
+    job_result: Arc<Mutex<Option<String>>>,
+    /// The last text submitted by a popup.
+    last_submitted: Option<String>,
+    /// Why the last popup couldn't be created, if it couldn't.
+    popup_error: Option<String>,
+    /// Whether every window is shown with light visuals.
+    light_mode: bool,
+}
+
+impl RootWindow {
+    pub fn request() -> NewWindowRequest<MyWindows> {
+        NewWindowRequest::new(
+            RootWindow {
+                num_popups_created: 0,
+                seconds_running: 0,
+                confirming_close: false,
+                job_result: Arc::new(Mutex::new(None)),
+                last_submitted: None,
+                popup_error: None,
+                light_mode: false,
+            }
+            .into(),
+            glutin::window::WindowBuilder::new()
+                .with_resizable(true)
+                .with_inner_size(glutin::dpi::LogicalSize {
+                    width: 800.0,
+                    height: 600.0,
+                })
+                .with_title("egui-multiwin root window"),
+        )
+        .with_key("root")
+    }
+
+    /// Reopens the root window of an earlier run, numbering new popups after the ones it opened.
+    pub fn restore(num_popups_created: u32) -> NewWindowRequest<MyWindows> {
+        let mut request = Self::request();
+        if let MyWindows::Root(root) = &mut request.window_state {
+            root.num_popups_created = num_popups_created;
+        }
+        request
+    }
+}
+
+impl TrackedWindow<MyWindows, AppEvent, AppState, AppMessage> for RootWindow {
+    fn update(
+        &mut self,
+        ctx: &egui::Context,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        let mut windows_to_create = vec![];
+        let mut close_responses = vec![];
+        let mut messages = vec![];
+
+        egui::SidePanel::left("my_side_panel").show(ctx, |ui| {
+            ui.heading("Hello World!");
+            if let Some(logo) = app.state().logo {
+                ui.image(logo, [32.0, 32.0]);
+            }
+            if ui.button("New popup").clicked() {
+                windows_to_create.push(PopupWindow::request(
+                    format!("popup window #{}", self.num_popups_created),
+                    app.handle(),
+                ));
+                self.num_popups_created += 1;
+            }
+            if let Some(error) = &self.popup_error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            if ui.button("Reset popups").clicked() {
+                messages.push((
+                    Recipient::Kind(|w| matches!(w, MyWindows::Popup(_))),
+                    AppMessage::ResetInput,
+                ));
+            }
+            if ui.button("Start background job").clicked() {
+                let job_result = self.job_result.clone();
+                let repaint = app.repaint_handle();
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_secs(2));
+                    *job_result.lock().unwrap() = Some("background job finished".to_string());
+                    // Wakes up the event loop and redraws just this window.
+                    repaint.request_repaint();
+                });
+            }
+            if ui.checkbox(&mut self.light_mode, "Light mode").changed() {
+                // Restyles the popups too, and every popup opened later.
+                let mut style = app.style().cloned().unwrap_or_default();
+                style.visuals = Some(if self.light_mode {
+                    egui::Visuals::light()
+                } else {
+                    egui::Visuals::dark()
+                });
+                app.set_style(style);
+            }
+            if ui.button("Save screenshot").clicked() {
+                app.save_screenshot(app.handle(), "root_window.png");
+            }
+            if ui.button("Quit").clicked() {
+                // Exit the whole application rather than just closing this window.
+                app.exit(0);
+            }
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(format!("number {}", app.state().button_press_count));
+            ui.label(format!("running for {} seconds", self.seconds_running));
+            if let Some(result) = self.job_result.lock().unwrap().as_ref() {
+                ui.label(result);
+            }
+            if let Some(submitted) = &self.last_submitted {
+                ui.label(format!("submitted: {}", submitted));
+            }
+
+            for (_, window) in app.other_windows() {
+                if let MyWindows::Popup(popup_window) = window {
+                    ui.add(egui::TextEdit::singleline(&mut popup_window.input));
+                }
+            }
+        });
+        if self.confirming_close {
+            egui::Window::new("Close the root window?")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Every popup will be closed along with it.");
+                    ui.horizontal(|ui| {
+                        if ui.button("Close").clicked() {
+                            close_responses.push((app.handle(), CloseRequestResponse::Allow));
+                            self.confirming_close = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            close_responses.push((app.handle(), CloseRequestResponse::Deny));
+                            self.confirming_close = false;
+                        }
+                    });
+                });
+        }
+
+        TrackedWindowControl {
+            windows_to_create,
+            close_responses,
+            messages,
+            ..Default::default()
+        }
+    }
+
+    fn on_user_event(
+        &mut self,
+        event: &AppEvent,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        let AppEvent::Tick = event;
+        self.seconds_running += 1;
+        app.repaint_handle().request_repaint();
+        TrackedWindowControl::default()
+    }
+
+    fn on_message(
+        &mut self,
+        message: Message<AppMessage>,
+        _app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        if let AppMessage::Submitted(text) = message.payload {
+            self.last_submitted = Some(text);
+        }
+        TrackedWindowControl::default()
+    }
+
+    fn on_window_created(
+        &mut self,
+        result: Result<WindowHandle, DisplayCreationError>,
+        _app: &mut AppCtx<MyWindows, AppState>,
+    ) -> TrackedWindowControl<MyWindows, AppMessage> {
+        self.popup_error = result
+            .err()
+            .map(|e| format!("couldn't open the popup: {}", e));
+        TrackedWindowControl::default()
+    }
+
+    fn on_close_requested(
+        &mut self,
+        app: &mut AppCtx<MyWindows, AppState>,
+    ) -> CloseRequestResponse {
+        if app.other_windows().next().is_some() {
+            self.confirming_close = true;
+            CloseRequestResponse::Defer
+        } else {
+            CloseRequestResponse::Allow
+        }
+    }
+}
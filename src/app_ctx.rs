@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use crate::{
+    app_style::AppStyle,
+    shared_textures::SharedTextures,
+    tracked_window::{RepaintHandle, WindowHandle},
+};
+
+/// Gives a `TrackedWindow` access to the rest of the application while it handles an event.
+pub struct AppCtx<'a, W, S = ()> {
+    handle: WindowHandle,
+    repaint: RepaintHandle,
+    other_windows: Vec<(WindowHandle, &'a mut W)>,
+    state: &'a mut S,
+    pub(crate) shared_textures: Option<&'a mut SharedTextures>,
+    style: Option<&'a AppStyle>,
+    pub(crate) windows_to_close: Vec<WindowHandle>,
+    pub(crate) windows_to_focus: Vec<WindowHandle>,
+    pub(crate) screenshots_to_save: Vec<(WindowHandle, PathBuf)>,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) new_style: Option<AppStyle>,
+}
+
+impl<'a, W, S> AppCtx<'a, W, S> {
+    pub(crate) fn new(
+        handle: WindowHandle,
+        repaint: RepaintHandle,
+        other_windows: Vec<(WindowHandle, &'a mut W)>,
+        state: &'a mut S,
+        shared_textures: Option<&'a mut SharedTextures>,
+        style: Option<&'a AppStyle>,
+    ) -> Self {
+        AppCtx {
+            handle,
+            repaint,
+            other_windows,
+            state,
+            shared_textures,
+            style,
+            windows_to_close: vec![],
+            windows_to_focus: vec![],
+            screenshots_to_save: vec![],
+            exit_code: None,
+            new_style: None,
+        }
+    }
+
+    /// The handle of the window handling the event.
+    pub fn handle(&self) -> WindowHandle {
+        self.handle
+    }
+
+    /// A handle that can repaint the window handling the event from any thread, e.g. once a
+    /// background job it started has finished.
+    pub fn repaint_handle(&self) -> RepaintHandle {
+        self.repaint.clone()
+    }
+
+    /// The application state shared by every window.
+    pub fn state(&self) -> &S {
+        self.state
+    }
+
+    /// The application state shared by every window.
+    pub fn state_mut(&mut self) -> &mut S {
+        self.state
+    }
+
+    /// The textures every window can draw, if the `MultiWindow` shares its GL context between
+    /// windows, see `MultiWindow::share_gl_context`.
+    pub fn shared_textures(&mut self) -> Option<&mut SharedTextures> {
+        self.shared_textures.as_deref_mut()
+    }
+
+    /// Looks up another window by its handle.
+    pub fn get(&self, handle: WindowHandle) -> Option<&W> {
+        self.other_windows
+            .iter()
+            .find(|(h, _)| *h == handle)
+            .map(|(_, w)| &**w)
+    }
+
+    /// Looks up another window by its handle.
+    pub fn get_mut(&mut self, handle: WindowHandle) -> Option<&mut W> {
+        self.other_windows
+            .iter_mut()
+            .find(|(h, _)| *h == handle)
+            .map(|(_, w)| &mut **w)
+    }
+
+    /// Iterates over every window except the one handling the event.
+    pub fn other_windows(&mut self) -> Box<dyn Iterator<Item = (WindowHandle, &mut W)> + '_> {
+        Box::new(self.other_windows.iter_mut().map(|(h, w)| (*h, &mut **w)))
+    }
+
+    /// Closes a window once the current event has been handled. This may be the window's own
+    /// handle.
+    pub fn close(&mut self, handle: WindowHandle) {
+        self.windows_to_close.push(handle);
+    }
+
+    /// Brings a window to the front and gives it input focus once the current event has been
+    /// handled.
+    pub fn focus(&mut self, handle: WindowHandle) {
+        self.windows_to_focus.push(handle);
+    }
+
+    /// Saves a screenshot of a window once the current event has been handled, in the format given
+    /// by the extension of `path`. This may be the window's own handle, in which case the
+    /// screenshot shows the frame being built.
+    pub fn save_screenshot(&mut self, handle: WindowHandle, path: impl Into<PathBuf>) {
+        self.screenshots_to_save.push((handle, path.into()));
+    }
+
+    /// The style every window is set up with, if the `MultiWindow` was given one. A style set with
+    /// `set_style` only shows up here once the current event has been handled.
+    pub fn style(&self) -> Option<&AppStyle> {
+        self.style
+    }
+
+    /// Restyles every window once the current event has been handled, see
+    /// `MultiWindow::set_style`. The whole style is replaced, so start from a clone of `style` to
+    /// change only part of it.
+    pub fn set_style(&mut self, style: AppStyle) {
+        self.new_style = Some(style);
+    }
+
+    /// Closes every window and exits the process with `code` once the current event has been
+    /// handled, regardless of the `MultiWindow`'s exit policy.
+    pub fn exit(&mut self, code: i32) {
+        self.exit_code.get_or_insert(code);
+    }
+}
@@ -0,0 +1,25 @@
+/// How the egui context of every window of a `MultiWindow` is set up, see
+/// `MultiWindow::set_style`. Anything left `None` is egui's default.
+#[derive(Clone, Debug, Default)]
+pub struct AppStyle {
+    pub fonts: Option<egui::FontDefinitions>,
+    /// Spacing, text styles, visuals and the like.
+    pub style: Option<egui::Style>,
+    /// Replaces the visuals of `style`, e.g. with `egui::Visuals::light()`.
+    pub visuals: Option<egui::Visuals>,
+    /// Physical pixels per point for every window, instead of the scale factor of the monitor each
+    /// window is on.
+    pub pixels_per_point: Option<f32>,
+}
+
+impl AppStyle {
+    /// Sets up an egui context with this style. The pixels per point are left to the window, which
+    /// knows its monitor's scale factor.
+    pub(crate) fn apply(&self, ctx: &egui::Context) {
+        ctx.set_fonts(self.fonts.clone().unwrap_or_default());
+        ctx.set_style(self.style.clone().unwrap_or_default());
+        if let Some(visuals) = &self.visuals {
+            ctx.set_visuals(visuals.clone());
+        }
+    }
+}
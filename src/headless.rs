@@ -0,0 +1,341 @@
+//! Running windows without a display, for automated UI tests.
+//!
+//! A headless `MultiWindow` (`MultiWindow::headless`) creates a `HeadlessWindow` for each window
+//! instead of a real window with a GL context. Frames are run with `MultiWindow::step` rather
+//! than by an event loop. Windows build their UI in `TrackedWindow::update` as usual, but nothing
+//! is painted until a screenshot is asked for, which renders the last frame in software.
+//! `TrackedWindow::handle_event` is never called, because there is no GL context to pass to it.
+//!
+//! Tests script user interaction by queueing input on a window's `HeadlessWindow`, found with
+//! `MultiWindow::headless_window_mut`. For example, after a first `step` has laid out a popup,
+//! `find_text("Increment")` gives the position of its button to `click`, and after another `step`
+//! the test can check the application state or the frame's `egui::FullOutput`.
+
+use std::{collections::HashMap, mem};
+
+use egui::{epaint::Primitive, Color32, Pos2, TextureId};
+use image::RgbaImage;
+
+use crate::tracked_window::ScreenshotError;
+
+/// The size of a headless window until it is changed with `HeadlessWindow::set_size`, in points.
+pub const DEFAULT_SIZE: egui::Vec2 = egui::vec2(800.0, 600.0);
+
+/// How much egui's clock advances with each frame, in seconds. Using a fixed step instead of the
+/// wall clock keeps animations the same from one test run to the next.
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// Stands in for the window and GL context of a window in a headless `MultiWindow`.
+pub struct HeadlessWindow {
+    egui_ctx: egui::Context,
+    size: egui::Vec2,
+    pixels_per_point: f32,
+    /// egui's clock, in seconds since the first frame.
+    time: f64,
+    /// Input for the next frame.
+    events: Vec<egui::Event>,
+    /// What the last frame produced, once there has been a frame.
+    output: Option<egui::FullOutput>,
+    /// Copies of the textures egui uploaded, to render screenshots with.
+    textures: HashMap<TextureId, Texture>,
+    /// Textures the last frame freed. They stay around until the next frame, because the last
+    /// frame may still use them.
+    textures_to_free: Vec<TextureId>,
+    /// Set by `request_close` until the next step.
+    close_requested: bool,
+}
+
+/// A texture egui uploaded, as premultiplied sRGBA pixels row by row.
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+}
+
+impl HeadlessWindow {
+    pub(crate) fn new() -> Self {
+        HeadlessWindow {
+            egui_ctx: egui::Context::default(),
+            size: DEFAULT_SIZE,
+            pixels_per_point: 1.0,
+            time: 0.0,
+            events: vec![],
+            output: None,
+            textures: HashMap::new(),
+            textures_to_free: vec![],
+            close_requested: false,
+        }
+    }
+
+    /// The egui context the window's frames are run with.
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// The size of the window, in points.
+    pub fn size(&self) -> egui::Vec2 {
+        self.size
+    }
+
+    /// Resizes the window, starting with the next frame.
+    pub fn set_size(&mut self, size: egui::Vec2) {
+        self.size = size;
+    }
+
+    /// The number of physical pixels per point.
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Changes the number of physical pixels per point, starting with the next frame.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        self.pixels_per_point = pixels_per_point;
+    }
+
+    /// Queues an input event for the next frame.
+    pub fn push_event(&mut self, event: egui::Event) {
+        self.events.push(event);
+    }
+
+    /// Queues a click with the primary mouse button at `pos`, in points, for the next frame.
+    /// egui finds what was clicked using the layout of the previous frame, so the window must
+    /// have run a frame since what is clicked on appeared.
+    pub fn click(&mut self, pos: egui::Pos2) {
+        self.push_event(egui::Event::PointerMoved(pos));
+        for pressed in [true, false] {
+            self.push_event(egui::Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed,
+                modifiers: egui::Modifiers::NONE,
+            });
+        }
+    }
+
+    /// Queues typing `text` into whatever has keyboard focus for the next frame.
+    pub fn type_text(&mut self, text: &str) {
+        self.push_event(egui::Event::Text(text.to_string()));
+    }
+
+    /// Queues pressing and releasing `key` for the next frame.
+    pub fn press_key(&mut self, key: egui::Key) {
+        for pressed in [true, false] {
+            self.push_event(egui::Event::Key {
+                key,
+                pressed,
+                modifiers: egui::Modifiers::NONE,
+            });
+        }
+    }
+
+    /// Asks to close the window on the next step, as clicking its close button would. The window
+    /// decides in `TrackedWindow::on_close_requested`.
+    pub fn request_close(&mut self) {
+        self.close_requested = true;
+    }
+
+    /// Whether `request_close` was called since the last time this was asked.
+    pub(crate) fn take_close_request(&mut self) -> bool {
+        std::mem::take(&mut self.close_requested)
+    }
+
+    /// What the window's last frame produced, or `None` before its first frame.
+    pub fn last_output(&self) -> Option<&egui::FullOutput> {
+        self.output.as_ref()
+    }
+
+    /// The shapes the window's last frame would have painted.
+    pub fn shapes(&self) -> &[egui::epaint::ClippedShape] {
+        match &self.output {
+            Some(output) => &output.shapes,
+            None => &[],
+        }
+    }
+
+    /// Where the last frame painted a piece of text exactly matching `text`, such as a button's
+    /// label. Useful for finding what to `click`.
+    pub fn find_text(&self, text: &str) -> Option<egui::Rect> {
+        fn find(shape: &egui::Shape, text: &str) -> Option<egui::Rect> {
+            match shape {
+                egui::Shape::Vec(shapes) => shapes.iter().find_map(|shape| find(shape, text)),
+                egui::Shape::Text(shape) if shape.galley.text() == text => {
+                    Some(shape.galley.rect.translate(shape.pos.to_vec2()))
+                }
+                _ => None,
+            }
+        }
+        self.shapes()
+            .iter()
+            .find_map(|egui::epaint::ClippedShape(_, shape)| find(shape, text))
+    }
+
+    /// Runs one egui frame with `run_ui` building the UI.
+    pub(crate) fn run_frame<R>(&mut self, run_ui: impl FnOnce(&egui::Context) -> R) -> R {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, self.size)),
+            pixels_per_point: Some(self.pixels_per_point),
+            time: Some(self.time),
+            events: mem::take(&mut self.events),
+            ..Default::default()
+        };
+        self.egui_ctx.begin_frame(input);
+        let result = run_ui(&self.egui_ctx);
+        // Nothing is painted now, the output is only kept around for tests and screenshots.
+        let output = self.egui_ctx.end_frame();
+        self.update_textures(&output.textures_delta);
+        self.output = Some(output);
+        self.time += FRAME_TIME;
+        result
+    }
+
+    fn update_textures(&mut self, delta: &egui::TexturesDelta) {
+        for id in self.textures_to_free.drain(..) {
+            self.textures.remove(&id);
+        }
+        for (id, delta) in &delta.set {
+            self.set_texture(*id, delta);
+        }
+        self.textures_to_free.extend_from_slice(&delta.free);
+    }
+
+    /// Creates a texture, or updates part of it.
+    pub(crate) fn set_texture(&mut self, id: TextureId, delta: &egui::epaint::ImageDelta) {
+        let (size, pixels): (_, Vec<_>) = match &delta.image {
+            egui::ImageData::Color(image) => (image.size, image.pixels.clone()),
+            egui::ImageData::Font(image) => (image.size, image.srgba_pixels(1.0).collect()),
+        };
+        match (delta.pos, self.textures.get_mut(&id)) {
+            (Some([x, y]), Some(texture)) => {
+                for (row, patch_row) in pixels.chunks_exact(size[0]).enumerate() {
+                    let start = (y + row) * texture.size[0] + x;
+                    texture.pixels[start..start + size[0]].copy_from_slice(patch_row);
+                }
+            }
+            _ => {
+                self.textures.insert(id, Texture { size, pixels });
+            }
+        }
+    }
+
+    /// Renders the last frame in software. The result is close to, but not exactly, what a real
+    /// window would show. Paint callbacks are skipped.
+    pub fn screenshot(&self) -> Result<RgbaImage, ScreenshotError> {
+        let output = self.output.as_ref().ok_or(ScreenshotError::NoFrame)?;
+        let width = (self.size.x * self.pixels_per_point).round() as u32;
+        let height = (self.size.y * self.pixels_per_point).round() as u32;
+
+        let background = Color32::from(crate::tracked_window::background_color());
+        let mut canvas = vec![premultiplied(background); width as usize * height as usize];
+        for primitive in self.egui_ctx.tessellate(output.shapes.clone()) {
+            let mesh = match primitive.primitive {
+                Primitive::Mesh(mesh) => mesh,
+                Primitive::Callback(_) => continue,
+            };
+            let clip_rect = egui::Rect::from_min_max(
+                (primitive.clip_rect.min.to_vec2() * self.pixels_per_point).to_pos2(),
+                (primitive.clip_rect.max.to_vec2() * self.pixels_per_point).to_pos2(),
+            )
+            .intersect(egui::Rect::from_min_size(
+                Pos2::ZERO,
+                egui::vec2(width as f32, height as f32),
+            ));
+            let texture = self.textures.get(&mesh.texture_id);
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertices = [
+                    mesh.vertices[triangle[0] as usize],
+                    mesh.vertices[triangle[1] as usize],
+                    mesh.vertices[triangle[2] as usize],
+                ];
+                fill_triangle(
+                    &mut canvas,
+                    width as usize,
+                    clip_rect,
+                    self.pixels_per_point,
+                    vertices,
+                    texture,
+                );
+            }
+        }
+
+        let pixels = canvas
+            .iter()
+            .flat_map(|pixel| pixel.map(|channel| (channel * 255.0).round() as u8))
+            .collect();
+        Ok(RgbaImage::from_raw(width, height, pixels).expect("the canvas was sized for the image"))
+    }
+}
+
+/// A color as premultiplied sRGBA, from 0 to 1.
+fn premultiplied(color: Color32) -> [f32; 4] {
+    color.to_array().map(|channel| channel as f32 / 255.0)
+}
+
+/// Blends a triangle of an egui mesh onto the canvas, sampling `texture` at the nearest texel.
+/// Meshes without a known texture are drawn as if it were white.
+fn fill_triangle(
+    canvas: &mut [[f32; 4]],
+    width: usize,
+    clip_rect: egui::Rect,
+    pixels_per_point: f32,
+    vertices: [egui::epaint::Vertex; 3],
+    texture: Option<&Texture>,
+) {
+    let [a, b, c] = vertices.map(|vertex| vertex.pos.to_vec2() * pixels_per_point);
+    let edge = |from: egui::Vec2, to: egui::Vec2, p: egui::Vec2| {
+        (to.x - from.x) * (p.y - from.y) - (to.y - from.y) * (p.x - from.x)
+    };
+    let area = edge(a, b, c);
+    if area == 0.0 {
+        return;
+    }
+
+    let bounds = egui::Rect::from_min_max(a.min(b).min(c).to_pos2(), a.max(b).max(c).to_pos2())
+        .intersect(clip_rect);
+    if !bounds.is_positive() {
+        return;
+    }
+    let colors = vertices.map(|vertex| premultiplied(vertex.color));
+    for y in bounds.min.y.floor() as usize..bounds.max.y.ceil() as usize {
+        for x in bounds.min.x.floor() as usize..bounds.max.x.ceil() as usize {
+            // Sample at the center of the pixel.
+            let p = egui::vec2(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [
+                edge(b, c, p) / area,
+                edge(c, a, p) / area,
+                edge(a, b, p) / area,
+            ];
+            if weights.iter().any(|weight| *weight < 0.0) {
+                continue;
+            }
+
+            let uv = vertices
+                .iter()
+                .zip(weights)
+                .fold(egui::Vec2::ZERO, |uv, (vertex, weight)| {
+                    uv + vertex.uv.to_vec2() * weight
+                });
+            let texel = match texture {
+                Some(texture) => {
+                    let tx = ((uv.x * texture.size[0] as f32) as usize).min(texture.size[0] - 1);
+                    let ty = ((uv.y * texture.size[1] as f32) as usize).min(texture.size[1] - 1);
+                    premultiplied(texture.pixels[ty * texture.size[0] + tx])
+                }
+                None => [1.0; 4],
+            };
+
+            let pixel = &mut canvas[y * width + x];
+            let mut source = [0.0; 4];
+            for (channel, source) in source.iter_mut().enumerate() {
+                let color: f32 = colors
+                    .iter()
+                    .zip(weights)
+                    .map(|(color, weight)| color[channel] * weight)
+                    .sum();
+                *source = color * texel[channel];
+            }
+            let alpha = source[3];
+            for (destination, source) in pixel.iter_mut().zip(source) {
+                *destination = source + *destination * (1.0 - alpha);
+            }
+        }
+    }
+}
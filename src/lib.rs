@@ -0,0 +1,17 @@
+//! Manage multiple `egui_glow` windows from a single `glutin` event loop.
+//!
+//! Implement `TrackedWindow` for each of your window types and for an enum wrapping them, then
+//! hand that enum to a `MultiWindow`. See `examples/multiwin` for a complete application.
+pub mod app_ctx;
+pub mod app_style;
+pub mod headless;
+pub mod message;
+pub mod multi_window;
+pub mod persistence;
+pub mod shared_textures;
+pub mod snapshot;
+pub mod tracked_window;
+
+pub use egui;
+pub use egui_glow;
+pub use glutin;
@@ -0,0 +1,52 @@
+use std::fmt;
+
+use crate::tracked_window::WindowHandle;
+
+/// A message one window sent to others, waiting in the recipient's mailbox until it is handed to
+/// `TrackedWindow::on_message`.
+#[derive(Clone, Debug)]
+pub struct Message<M> {
+    /// The window that sent the message.
+    pub from: WindowHandle,
+    pub payload: M,
+}
+
+/// Who a message sent through `TrackedWindowControl::messages` is delivered to. The sender never
+/// receives its own messages.
+pub enum Recipient<W> {
+    /// A single window.
+    Window(WindowHandle),
+    /// Every window the function returns true for, e.g. `|w| matches!(w, MyWindows::Popup(_))`.
+    Kind(fn(&W) -> bool),
+    /// Every window.
+    Broadcast,
+}
+
+impl<W> Recipient<W> {
+    /// Whether a window other than the sender should receive the message.
+    pub(crate) fn includes(&self, handle: WindowHandle, window: &W) -> bool {
+        match self {
+            Recipient::Window(recipient) => *recipient == handle,
+            Recipient::Kind(is_kind) => is_kind(window),
+            Recipient::Broadcast => true,
+        }
+    }
+}
+
+impl<W> Clone for Recipient<W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W> Copy for Recipient<W> {}
+
+impl<W> fmt::Debug for Recipient<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recipient::Window(handle) => f.debug_tuple("Window").field(handle).finish(),
+            Recipient::Kind(_) => f.write_str("Kind"),
+            Recipient::Broadcast => f.write_str("Broadcast"),
+        }
+    }
+}
@@ -1,137 +1,1217 @@
-use glutin::{
-    event_loop::{ControlFlow, EventLoop},
-    platform::run_return::EventLoopExtRunReturn,
+use std::{
+    collections::HashMap,
+    mem,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
 };
 
-use crate::tracked_window::{DisplayCreationError, TrackedWindowContainer};
-use crate::windows::MyWindows;
+use glutin::event::Event;
+use glutin::event_loop::{
+    ControlFlow, EventLoop, EventLoopBuilder, EventLoopClosed, EventLoopProxy,
+    EventLoopWindowTarget,
+};
+use glutin::window::WindowId;
+use image::RgbaImage;
+
+#[cfg(feature = "persistence")]
+use crate::persistence::EguiMemoryStore;
+use crate::{
+    app_ctx::AppCtx,
+    app_style::AppStyle,
+    headless::HeadlessWindow,
+    message::{Message, Recipient},
+    persistence::{
+        session_to_ron, GeometryStore, PersistenceError, Session, SessionState, WindowGeometry,
+    },
+    shared_textures::SharedTextures,
+    tracked_window::{
+        CloseRequestResponse, DisplayCreationError, IndeterminateWindowedContext, RepaintHandle,
+        ScreenshotError, TrackedWindow, TrackedWindowContainer, WindowError, WindowHandle,
+    },
+};
 
 /// Manages multiple `TrackedWindow`s by forwarding events to them.
-pub struct MultiWindow {
-    windows: Vec<TrackedWindowContainer>,
+///
+/// `W` is the application's window type, usually an enum with one variant per kind of window.
+/// `T` is the type of user events that can be sent to the event loop through a
+/// `MultiWindowProxy`. `S` is the application state that the `MultiWindow` owns and shares with
+/// every window through its `AppCtx`. `M` is the type of messages windows send each other through
+/// `TrackedWindowControl::messages`.
+pub struct MultiWindow<W, T: 'static = (), S = (), M = ()> {
+    /// Only `None` once `run` has taken it, or if the `MultiWindow` is headless.
+    event_loop: Option<EventLoop<LoopEvent<T>>>,
+    proxy: MultiWindowProxy<T>,
+    /// Shared by every window's `RepaintHandle`.
+    wake_for_repaint: Arc<dyn Fn(WindowHandle) + Send + Sync>,
+    windows: Vec<TrackedWindowContainer<W, M>>,
+    next_handle: u64,
+    exit_policy: ExitPolicy,
+    /// Decides what happens to windows that fail to handle an event.
+    on_error: Box<ErrorCallback>,
+    /// The geometry of keyed windows, applied when they are created.
+    geometry: GeometryStore,
+    /// Where `geometry` is saved, if it is saved at all.
+    geometry_file: Option<PathBuf>,
+    /// The egui memory of keyed windows, restored when they are created.
+    #[cfg(feature = "persistence")]
+    egui_memory: EguiMemoryStore,
+    /// Where `egui_memory` is saved, if it is saved at all.
+    #[cfg(feature = "persistence")]
+    egui_memory_file: Option<PathBuf>,
+    /// Saves the open windows when the event loop exits, if `remember_session` was called.
+    session: Option<SessionSaver<W, M>>,
+    /// A hidden context every window's context shares its objects with, once `share_gl_context`
+    /// has been called. Headless `MultiWindow`s have none.
+    shared_context: Option<glutin::Context<glutin::NotCurrent>>,
+    /// Only set once `share_gl_context` has been called.
+    shared_textures: Option<SharedTextures>,
+    /// The painters of the closed windows that drew shared textures, destroyed along with the
+    /// shared textures once no open window draws them.
+    retired_painters: Vec<egui_glow::Painter>,
+    /// Applied to every window, once `set_style` has been called.
+    style: Option<Arc<AppStyle>>,
+    /// The exit code sent by a `MultiWindowProxy`, until the next event exits with it.
+    requested_exit: Option<i32>,
+    /// The code the event loop exited with, once it has.
+    exited: Option<i32>,
+    state: S,
+}
+
+/// Where and how to save the session, see `MultiWindow::remember_session`.
+struct SessionSaver<W, M> {
+    path: PathBuf,
+    /// `session_to_ron` for the window type, which is only known to implement `SessionState` where
+    /// the saver is made.
+    to_ron: SessionToRon<W, M>,
+}
+
+type SessionToRon<W, M> = fn(&[TrackedWindowContainer<W, M>]) -> Result<String, PersistenceError>;
+
+/// Events sent to the event loop from outside of it.
+enum LoopEvent<T> {
+    /// Passed on to the windows as `Event::UserEvent`.
+    User(T),
+    /// Sent by a `RepaintHandle`.
+    Repaint(WindowHandle),
+    /// Sent by `MultiWindowProxy::exit`.
+    Exit(i32),
+}
+
+/// Wakes up the event loop and sends it user events from any thread, including after `run` has
+/// been called. Every window receives the events through `TrackedWindow::on_user_event`, and
+/// windows with a display also as `Event::UserEvent` in `TrackedWindow::handle_event`.
+pub struct MultiWindowProxy<T: 'static> {
+    target: ProxyTarget<T>,
 }
 
-impl MultiWindow {
-    /// Creates a new `MultiWindow`.
+/// Where a `MultiWindowProxy` sends its events.
+enum ProxyTarget<T: 'static> {
+    EventLoop(EventLoopProxy<LoopEvent<T>>),
+    /// Headless `MultiWindow`s have no event loop, so events wait here for the next `step`.
+    Headless(Arc<Mutex<Vec<LoopEvent<T>>>>),
+}
+
+impl<T: 'static> Clone for MultiWindowProxy<T> {
+    fn clone(&self) -> Self {
+        let target = match &self.target {
+            ProxyTarget::EventLoop(proxy) => ProxyTarget::EventLoop(proxy.clone()),
+            ProxyTarget::Headless(queue) => ProxyTarget::Headless(queue.clone()),
+        };
+        MultiWindowProxy { target }
+    }
+}
+
+impl<T: 'static> MultiWindowProxy<T> {
+    /// Sends a user event to the event loop. Fails, handing the event back, if the event loop has
+    /// already exited.
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.send(LoopEvent::User(event))
+            .map_err(|EventLoopClosed(event)| match event {
+                LoopEvent::User(event) => EventLoopClosed(event),
+                _ => unreachable!("a user event was sent"),
+            })
+    }
+
+    /// Closes every window and exits the process with `code`, as `AppCtx::exit` does. Under
+    /// `ExitPolicy::Explicit` this is how the application exits once its last window has closed.
+    /// Fails if the event loop has already exited.
+    pub fn exit(&self, code: i32) -> Result<(), EventLoopClosed<i32>> {
+        self.send(LoopEvent::Exit(code))
+            .map_err(|_| EventLoopClosed(code))
+    }
+
+    /// Sends any kind of event to wherever this proxy's events go.
+    fn send(&self, event: LoopEvent<T>) -> Result<(), EventLoopClosed<LoopEvent<T>>> {
+        match &self.target {
+            ProxyTarget::EventLoop(proxy) => proxy.send_event(event),
+            ProxyTarget::Headless(queue) => match queue.lock() {
+                Ok(mut queue) => {
+                    queue.push(event);
+                    Ok(())
+                }
+                Err(_) => Err(EventLoopClosed(event)),
+            },
+        }
+    }
+}
+
+type ErrorCallback = dyn FnMut(WindowHandle, &WindowError) -> WindowErrorResponse;
+
+/// What happens to a window that failed to handle an event, as decided by `MultiWindow::on_error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowErrorResponse {
+    /// Close the window along with its children. Every other window keeps running.
+    Close,
+    /// Keep the window, and redraw it to try again.
+    Retry,
+}
+
+/// Decides when `MultiWindow::run` stops the event loop and exits the process. Regardless of the
+/// policy, any window can exit immediately with `AppCtx::exit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExitPolicy {
+    /// Exit once every window has closed.
+    #[default]
+    LastWindowClosed,
+    /// Exit once the given window has closed, even if other windows are still open.
+    PrimaryWindowClosed(WindowHandle),
+    /// Keep running with no windows open. Only exit when a window calls `AppCtx::exit` or a
+    /// `MultiWindowProxy` calls `MultiWindowProxy::exit`.
+    Explicit,
+}
+
+impl<W, T: Send, S: Default, M> Default for MultiWindow<W, T, S, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W, T: Send, S: Default, M> MultiWindow<W, T, S, M> {
+    /// Creates a new `MultiWindow` along with the event loop it will run on, starting from the
+    /// default application state.
     pub fn new() -> Self {
-        MultiWindow { windows: vec![] }
+        Self::with_state(S::default())
+    }
+
+    /// Creates a `MultiWindow` that needs no display, starting from the default application
+    /// state. See `headless_with_state`.
+    pub fn headless() -> Self {
+        Self::headless_with_state(S::default())
+    }
+}
+
+impl<W, T: Send, S, M> MultiWindow<W, T, S, M> {
+    /// Creates a new `MultiWindow` along with the event loop it will run on.
+    pub fn with_state(state: S) -> Self {
+        let event_loop = EventLoopBuilder::with_user_event().build();
+        let proxy = MultiWindowProxy {
+            target: ProxyTarget::EventLoop(event_loop.create_proxy()),
+        };
+        let repaint_proxy = Mutex::new(event_loop.create_proxy());
+        let wake_for_repaint = Arc::new(move |handle| {
+            if let Ok(proxy) = repaint_proxy.lock() {
+                // Nothing is left to repaint if the event loop has exited.
+                let _ = proxy.send_event(LoopEvent::Repaint(handle));
+            }
+        });
+        MultiWindow {
+            event_loop: Some(event_loop),
+            proxy,
+            wake_for_repaint,
+            windows: vec![],
+            next_handle: 0,
+            exit_policy: ExitPolicy::default(),
+            on_error: Box::new(close_on_error),
+            geometry: GeometryStore::default(),
+            geometry_file: None,
+            #[cfg(feature = "persistence")]
+            egui_memory: EguiMemoryStore::default(),
+            #[cfg(feature = "persistence")]
+            egui_memory_file: None,
+            session: None,
+            shared_context: None,
+            shared_textures: None,
+            retired_painters: vec![],
+            style: None,
+            requested_exit: None,
+            exited: None,
+            state,
+        }
+    }
+
+    /// Creates a `MultiWindow` that needs no display, for tests. Its windows are `HeadlessWindow`s
+    /// that nothing is painted to, and frames are run with `step` instead of `run`.
+    pub fn headless_with_state(state: S) -> Self {
+        MultiWindow {
+            event_loop: None,
+            proxy: MultiWindowProxy {
+                target: ProxyTarget::Headless(Arc::new(Mutex::new(vec![]))),
+            },
+            // Every window runs a frame on each step anyway.
+            wake_for_repaint: Arc::new(|_| ()),
+            windows: vec![],
+            next_handle: 0,
+            exit_policy: ExitPolicy::default(),
+            on_error: Box::new(close_on_error),
+            geometry: GeometryStore::default(),
+            geometry_file: None,
+            #[cfg(feature = "persistence")]
+            egui_memory: EguiMemoryStore::default(),
+            #[cfg(feature = "persistence")]
+            egui_memory_file: None,
+            session: None,
+            shared_context: None,
+            shared_textures: None,
+            retired_painters: vec![],
+            style: None,
+            requested_exit: None,
+            exited: None,
+            state,
+        }
+    }
+
+    /// Whether this `MultiWindow` was created by `headless` or `headless_with_state`.
+    pub fn is_headless(&self) -> bool {
+        matches!(self.proxy.target, ProxyTarget::Headless(_))
+    }
+
+    /// The application state shared by every window.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// The application state shared by every window.
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    /// Sets what happens to a window that fails to handle an event, e.g. because its GL context
+    /// was lost. By default the error is logged and the window is closed.
+    pub fn on_error(
+        &mut self,
+        on_error: impl FnMut(WindowHandle, &WindowError) -> WindowErrorResponse + 'static,
+    ) {
+        self.on_error = Box::new(on_error);
+    }
+
+    /// Sets when the event loop should exit. Defaults to `ExitPolicy::LastWindowClosed`.
+    pub fn set_exit_policy(&mut self, exit_policy: ExitPolicy) {
+        self.exit_policy = exit_policy;
+    }
+
+    /// Remembers where windows created with `NewWindowRequest::with_key` were and how big they
+    /// were in `path`. The geometry saved there by an earlier run is applied to windows created
+    /// from now on, and the file is updated when the event loop exits. Windows of a headless
+    /// `MultiWindow` are neither placed nor remembered.
+    ///
+    /// If the file can't be read, the error is returned and the windows start from scratch; the
+    /// file is still overwritten on exit.
+    pub fn remember_geometry(&mut self, path: impl Into<PathBuf>) -> Result<(), PersistenceError> {
+        let path = path.into();
+        let loaded = GeometryStore::load(&path);
+        self.geometry_file = Some(path);
+        self.geometry = loaded?;
+        Ok(())
+    }
+
+    /// The geometry remembered for keyed windows, including what was loaded by
+    /// `remember_geometry`. Windows record their geometry here when they close.
+    pub fn geometry(&self) -> &GeometryStore {
+        &self.geometry
+    }
+
+    /// The geometry remembered for keyed windows.
+    pub fn geometry_mut(&mut self) -> &mut GeometryStore {
+        &mut self.geometry
+    }
+
+    /// Records the geometry of every open keyed window and writes what is remembered to the file
+    /// given to `remember_geometry`. Does nothing if there is none. Called on exit, but can be
+    /// called earlier to survive a crash.
+    pub fn save_geometry(&mut self) -> Result<(), PersistenceError> {
+        let path = match &self.geometry_file {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        for container in &self.windows {
+            record_geometry(&mut self.geometry, container);
+        }
+        self.geometry.save(path)
+    }
+
+    /// Remembers the `egui::Memory` of windows created with `NewWindowRequest::with_key` in
+    /// `path`, so collapsed headers, scroll positions, panel sizes and the like survive a restart.
+    /// Works like `remember_geometry`, including for headless windows.
+    #[cfg(feature = "persistence")]
+    pub fn remember_egui_memory(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> Result<(), PersistenceError> {
+        let path = path.into();
+        let loaded = EguiMemoryStore::load(&path);
+        self.egui_memory_file = Some(path);
+        self.egui_memory = loaded?;
+        Ok(())
+    }
+
+    /// The egui memory remembered for keyed windows. Windows record their memory here when they
+    /// close.
+    #[cfg(feature = "persistence")]
+    pub fn egui_memory(&self) -> &EguiMemoryStore {
+        &self.egui_memory
+    }
+
+    /// The egui memory remembered for keyed windows.
+    #[cfg(feature = "persistence")]
+    pub fn egui_memory_mut(&mut self) -> &mut EguiMemoryStore {
+        &mut self.egui_memory
+    }
+
+    /// Records the egui memory of every open keyed window and writes what is remembered to the
+    /// file given to `remember_egui_memory`. Does nothing if there is none.
+    #[cfg(feature = "persistence")]
+    pub fn save_egui_memory(&mut self) -> Result<(), PersistenceError> {
+        let path = match &self.egui_memory_file {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        for container in &self.windows {
+            record_egui_memory(&mut self.egui_memory, container);
+        }
+        self.egui_memory.save(path)
+    }
+
+    /// Creates the GL context of every window added from now on so that it shares textures and
+    /// other GL objects with the others, and enables `shared_textures`. Windows added before aren't
+    /// part of it, and every window that is must ask for the same GL version and profile in its
+    /// `GlConfig`. Does nothing if the GL context is already shared. Headless windows have no GL
+    /// context, but can draw shared textures all the same.
+    pub fn share_gl_context(&mut self) -> Result<(), DisplayCreationError> {
+        if self.shared_textures.is_some() {
+            return Ok(());
+        }
+        if !self.is_headless() {
+            // A hidden context holds on to the shared objects, so they outlive whichever window
+            // created them.
+            let context = glutin::ContextBuilder::new()
+                .with_srgb(true)
+                .build_headless(self.event_loop(), glutin::dpi::PhysicalSize::new(1, 1))?;
+            self.shared_context = Some(context);
+        }
+        self.shared_textures = Some(SharedTextures::new());
+        Ok(())
+    }
+
+    /// The textures every window can draw, once `share_gl_context` has been called.
+    pub fn shared_textures(&mut self) -> Option<&mut SharedTextures> {
+        self.shared_textures.as_mut()
+    }
+
+    /// Sets up the egui context of every window with `style`: the windows that are open now and
+    /// every window created from now on. A window that changes its own egui context's style keeps
+    /// the change until the next call.
+    pub fn set_style(&mut self, style: AppStyle) {
+        let style = Arc::new(style);
+        for container in &mut self.windows {
+            container.set_style(style.clone());
+        }
+        self.style = Some(style);
+    }
+
+    /// The style given to `set_style`, if any.
+    pub fn style(&self) -> Option<&AppStyle> {
+        self.style.as_deref()
+    }
+
+    /// Creates a proxy that can wake up the event loop and send it user events from any thread.
+    pub fn create_proxy(&self) -> MultiWindowProxy<T> {
+        self.proxy.clone()
+    }
+
+    /// A handle that can repaint the given window from any thread.
+    pub fn repaint_handle(&self, handle: WindowHandle) -> Option<RepaintHandle> {
+        self.windows
+            .iter()
+            .find(|container| container.handle == handle)
+            .map(|container| container.repaint.clone())
+    }
+
+    /// Adds a new `TrackedWindow` to the `MultiWindow`, returning the handle it can be addressed by.
+    pub fn add(
+        &mut self,
+        window: NewWindowRequest<W>,
+    ) -> Result<WindowHandle, DisplayCreationError> {
+        if self.is_headless() {
+            return self.add_running(window, None);
+        }
+        let handle = self.allocate_handle();
+        let repaint = RepaintHandle::new(handle, self.wake_for_repaint.clone());
+        let window = restore_geometry(&self.geometry, window, self.event_loop());
+        let container = TrackedWindowContainer::create(
+            handle,
+            window,
+            self.event_loop(),
+            repaint,
+            self.shared_context.as_ref(),
+        )?;
+        self.track(container);
+        Ok(handle)
+    }
+
+    /// Adds a new `TrackedWindow` while the event loop is running. `event_loop` is `None` for
+    /// headless `MultiWindow`s.
+    fn add_running(
+        &mut self,
+        window: NewWindowRequest<W>,
+        event_loop: Option<&EventLoopWindowTarget<LoopEvent<T>>>,
+    ) -> Result<WindowHandle, DisplayCreationError> {
+        let handle = self.allocate_handle();
+        let repaint = RepaintHandle::new(handle, self.wake_for_repaint.clone());
+        let container = match event_loop {
+            Some(event_loop) => {
+                let window = restore_geometry(&self.geometry, window, event_loop);
+                TrackedWindowContainer::create(
+                    handle,
+                    window,
+                    event_loop,
+                    repaint,
+                    self.shared_context.as_ref(),
+                )?
+            }
+            None => TrackedWindowContainer::create_headless(
+                handle,
+                window,
+                repaint,
+                self.shared_textures.is_some(),
+            )?,
+        };
+        self.track(container);
+        Ok(handle)
+    }
+
+    /// Starts tracking a newly created window.
+    fn track(&mut self, mut container: TrackedWindowContainer<W, M>) {
+        #[cfg(feature = "persistence")]
+        if let Some(memory) = container
+            .key
+            .as_deref()
+            .and_then(|key| self.egui_memory.get(key))
+        {
+            container.restore_egui_memory(memory.clone());
+        }
+        // Remembered egui memory holds a style of its own, which the `MultiWindow`'s replaces.
+        if let Some(style) = &self.style {
+            container.set_style(style.clone());
+        }
+        container.span.in_scope(|| tracing::info!("window created"));
+        self.windows.push(container);
+    }
+
+    /// The stand-in for a window of a headless `MultiWindow`, to resize it or look at its egui
+    /// context. `None` if there is no such window, or the `MultiWindow` isn't headless.
+    pub fn headless_window_mut(&mut self, handle: WindowHandle) -> Option<&mut HeadlessWindow> {
+        self.windows
+            .iter_mut()
+            .find(|container| container.handle == handle)
+            .and_then(|container| match &mut container.gl_window {
+                IndeterminateWindowedContext::Headless(headless) => Some(headless),
+                _ => None,
+            })
+    }
+
+    /// Looks up a window by its handle.
+    pub fn get(&self, handle: WindowHandle) -> Option<&W> {
+        self.windows
+            .iter()
+            .find(|container| container.handle == handle)
+            .map(|container| &container.window)
+    }
+
+    /// Looks up a window by its handle.
+    pub fn get_mut(&mut self, handle: WindowHandle) -> Option<&mut W> {
+        self.windows
+            .iter_mut()
+            .find(|container| container.handle == handle)
+            .map(|container| &mut container.window)
+    }
+
+    /// Closes a window along with all of its descendants. Each child is told that its parent is
+    /// closing before it is closed itself. Returns false if there was no window with that handle.
+    pub fn close(&mut self, handle: WindowHandle) -> bool
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let index = match self
+            .windows
+            .iter()
+            .position(|container| container.handle == handle)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+        let mut container = self.windows.remove(index);
+
+        for child in self.children(handle) {
+            if let Some(child_window) = self.get_mut(child) {
+                child_window.on_parent_closed(handle);
+            }
+            self.close(child);
+        }
+
+        record_geometry(&mut self.geometry, &container);
+        #[cfg(feature = "persistence")]
+        record_egui_memory(&mut self.egui_memory, &container);
+        container.destroy();
+        self.retired_painters
+            .extend(container.retired_painter.take());
+        if !self
+            .windows
+            .iter()
+            .any(|container| container.draws_shared_textures())
+        {
+            self.destroy_retired_painters();
+        }
+        container.span.in_scope(|| tracing::info!("window closed"));
+        true
+    }
+
+    /// Answers a deferred close request. Does nothing if the window has no pending close request.
+    fn resolve_close(&mut self, handle: WindowHandle, response: CloseRequestResponse)
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let pending = self
+            .windows
+            .iter_mut()
+            .find(|container| container.handle == handle && container.close_pending);
+        if let Some(container) = pending {
+            match response {
+                CloseRequestResponse::Allow => {
+                    self.close(handle);
+                }
+                CloseRequestResponse::Deny => container.close_pending = false,
+                CloseRequestResponse::Defer => (),
+            }
+        }
+    }
+
+    /// Puts a message in the mailbox of every window it is addressed to, other than the sender,
+    /// and wakes them up to receive it.
+    fn deliver(&mut self, from: WindowHandle, recipient: Recipient<W>, payload: M)
+    where
+        M: Clone,
+    {
+        let recipients = self.windows.iter_mut().filter(|container| {
+            container.handle != from && recipient.includes(container.handle, &container.window)
+        });
+        for container in recipients {
+            container.mailbox.push(Message {
+                from,
+                payload: payload.clone(),
+            });
+            container.repaint.request_repaint();
+        }
+    }
+
+    /// The windows whose parent is the given window.
+    pub fn children(&self, handle: WindowHandle) -> Vec<WindowHandle> {
+        self.windows
+            .iter()
+            .filter(|container| container.parent == Some(handle))
+            .map(|container| container.handle)
+            .collect()
     }
 
-    /// Adds a new `TrackedWindow` to the `MultiWindow`.
-    pub fn add<TE>(
+    /// Captures what a window showed in its last frame. See `TrackedWindowContainer::screenshot`.
+    pub fn screenshot(&mut self, handle: WindowHandle) -> Result<RgbaImage, ScreenshotError> {
+        self.windows
+            .iter_mut()
+            .find(|container| container.handle == handle)
+            .ok_or(ScreenshotError::UnknownWindow(handle))?
+            .screenshot()
+    }
+
+    /// Saves a `screenshot` of a window, in the format given by the extension of `path`.
+    pub fn save_screenshot(
         &mut self,
-        window: NewWindowRequest,
-        event_loop: &glutin::event_loop::EventLoopWindowTarget<TE>,
-    ) -> Result<(), DisplayCreationError> {
-        Ok(self.windows.push(TrackedWindowContainer::create(
-            window.window_state,
-            window.builder,
-            event_loop,
-        )?))
-    }
-
-    /// Runs the event loop until all `TrackedWindow`s are closed.
-    pub fn run(mut multi_window: MultiWindow, event_loop: EventLoop<()>) {
+        handle: WindowHandle,
+        path: impl AsRef<Path>,
+    ) -> Result<(), ScreenshotError> {
+        self.screenshot(handle)?.save(path)?;
+        Ok(())
+    }
+
+    /// Brings a window to the front and gives it input focus. Returns false if there was no window
+    /// with that handle.
+    pub fn focus(&mut self, handle: WindowHandle) -> bool {
+        let window = self
+            .windows
+            .iter()
+            .find(|container| container.handle == handle)
+            .and_then(|container| container.gl_window.window());
+        match window {
+            Some(window) => {
+                window.focus_window();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn allocate_handle(&mut self) -> WindowHandle {
+        let handle = WindowHandle(self.next_handle);
+        self.next_handle += 1;
+        handle
+    }
+
+    fn event_loop(&self) -> &EventLoop<LoopEvent<T>> {
+        self.event_loop.as_ref().expect(
+            "the event loop is only taken by run, which consumes the MultiWindow, and headless \
+             MultiWindows never use it",
+        )
+    }
+}
+
+impl<W: SessionState, T: Send, S, M> MultiWindow<W, T, S, M> {
+    /// Saves which windows are open, and which window owns which, to `path` when the event loop
+    /// exits, so `restore_session` can reopen them on the next launch. The windows are saved as
+    /// they were before the event that ended the run closed any, so when closing the primary
+    /// window ends it, the windows it owned are still part of the session.
+    pub fn remember_session(&mut self, path: impl Into<PathBuf>) {
+        self.session = Some(SessionSaver {
+            path: path.into(),
+            to_ron: session_to_ron::<W, M>,
+        });
+    }
+
+    /// Saves which windows are open right now to `path`.
+    pub fn save_session(&self, path: impl AsRef<Path>) -> Result<(), PersistenceError> {
+        std::fs::write(path, session_to_ron(&self.windows)?)?;
+        Ok(())
+    }
+
+    /// Reopens the windows of a session saved to `path`, returning their handles in the order
+    /// they were created. There are none if the file doesn't exist, e.g. on the first launch.
+    /// A window that can't be created is skipped, along with the windows it owned.
+    pub fn restore_session(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<WindowHandle>, PersistenceError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let session: Session<W::Saved> = ron::from_str(&std::fs::read_to_string(path)?)?;
+
+        // The handles of the last run, mapped to those of the reopened windows.
+        let mut reopened = HashMap::new();
+        let mut handles = vec![];
+        for saved in session.windows {
+            let parent = match saved.parent {
+                Some(parent) => match reopened.get(&parent) {
+                    Some(&parent) => Some(parent),
+                    None => continue,
+                },
+                None => None,
+            };
+            let mut request = match W::restore_session(saved.state, parent) {
+                Some(request) => request,
+                None => continue,
+            };
+            request.parent = parent;
+            match self.add(request) {
+                Ok(handle) => {
+                    reopened.insert(saved.handle, handle);
+                    handles.push(handle);
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "couldn't reopen a window of the last session")
+                }
+            }
+        }
+        Ok(handles)
+    }
+}
+
+impl<W, T, S, M> MultiWindow<W, T, S, M>
+where
+    W: TrackedWindow<W, T, S, M> + 'static,
+    T: std::fmt::Debug + Send + 'static,
+    S: 'static,
+    M: Clone + 'static,
+{
+    /// Runs the event loop until the exit policy says to stop, or a window asks to exit.
+    ///
+    /// Panics if the `MultiWindow` is headless. Those are driven with `step` instead.
+    pub fn run(mut multi_window: MultiWindow<W, T, S, M>) {
+        let event_loop = multi_window.event_loop.take().expect(
+            "run consumes the MultiWindow, so the event loop is present unless it is headless",
+        );
         event_loop.run(move |event, event_loop_window_target, flow| {
-            println!("handling event {:?}", event);
-            let mut handled_windows = vec![];
-            let mut window_control_flow = vec![];
-            while let Some(mut window) = multi_window.windows.pop() {
-                if window.is_event_for_window(&event) {
-                    // Collect all the other windows.
-                    let other_windows = multi_window.windows.iter_mut().chain(handled_windows.iter_mut()).map(|container| &mut container.window).collect();
-                    let window_control = window.handle_event_outer(&event, &event_loop_window_target, other_windows);
-                    match window_control.requested_control_flow {
-                        ControlFlow::Exit => {
-                            println!("window requested exit. Instead of sending the exit for everyone, just get rid of this one.");
-                            window_control_flow.push(ControlFlow::Exit);
-                            continue;
-                            //*flow = ControlFlow::Exit
-                        },
-                        requested_flow => {
-                            window_control_flow.push(requested_flow);
+            let event = match event.map_nonuser_event() {
+                Ok(event) => event,
+                Err(Event::UserEvent(LoopEvent::User(event))) => Event::UserEvent(event),
+                Err(Event::UserEvent(LoopEvent::Repaint(handle))) => {
+                    // Only the window that asked is redrawn, everything else stays as it was.
+                    let window = multi_window
+                        .windows
+                        .iter_mut()
+                        .find(|window| window.handle == handle);
+                    if let Some(window) = window {
+                        window.redraw_if_requested();
+                    }
+                    return;
+                }
+                Err(Event::UserEvent(LoopEvent::Exit(code))) => {
+                    // Handled along with the next event, which winit sends in the same iteration.
+                    multi_window.requested_exit.get_or_insert(code);
+                    return;
+                }
+                Err(_) => unreachable!("only user events can't be mapped"),
+            };
+            multi_window.handle_loop_event(event, Some(event_loop_window_target), flow);
+        });
+    }
+
+    /// Steps a headless `MultiWindow` `frames` times, stopping early if the application exits.
+    /// Returns the exit code if it did.
+    pub fn step_frames(&mut self, frames: usize) -> Option<i32> {
+        (0..frames).find_map(|_| self.step())
+    }
+
+    /// Runs one frame of a headless `MultiWindow`. User events sent through its proxies since the
+    /// last step are handed to `TrackedWindow::on_user_event` first, then every window runs
+    /// `TrackedWindow::update` once. Returns the exit code once the application has exited, at
+    /// which point every window is closed.
+    ///
+    /// Panics if the `MultiWindow` isn't headless.
+    pub fn step(&mut self) -> Option<i32> {
+        let queue = match &self.proxy.target {
+            ProxyTarget::Headless(queue) => queue.clone(),
+            ProxyTarget::EventLoop(_) => panic!("only headless MultiWindows can be stepped"),
+        };
+        let mut flow = ControlFlow::Wait;
+        let events = match queue.lock() {
+            Ok(mut queue) => mem::take(&mut *queue),
+            Err(_) => vec![],
+        };
+        let events = events.into_iter().filter_map(|event| match event {
+            LoopEvent::User(event) => Some(Event::UserEvent(event)),
+            // Headless windows are redrawn on every step anyway.
+            LoopEvent::Repaint(_) => None,
+            LoopEvent::Exit(code) => {
+                self.requested_exit.get_or_insert(code);
+                None
+            }
+        });
+        // Headless windows have no IDs to check this against, so the dummy is never passed to
+        // winit and every window runs the frame.
+        let redraw = Event::RedrawRequested(unsafe { WindowId::dummy() });
+        for event in events.collect::<Vec<_>>().into_iter().chain(Some(redraw)) {
+            self.handle_loop_event(event, None, &mut flow);
+            if let ControlFlow::ExitWithCode(code) = flow {
+                return Some(code);
+            }
+        }
+        None
+    }
+
+    /// Lets every window the event is for handle it, then carries out what the windows asked for:
+    /// creating, closing and focusing windows, delivering messages and exiting. `event_loop` is
+    /// `None` for headless `MultiWindow`s.
+    fn handle_loop_event(
+        &mut self,
+        event: Event<'_, T>,
+        event_loop: Option<&EventLoopWindowTarget<LoopEvent<T>>>,
+        flow: &mut ControlFlow,
+    ) {
+        tracing::trace!(?event, "handling event");
+        // winit keeps calling the handler for the rest of the iteration after an exit, and once
+        // more with `LoopDestroyed`. Every window is closed and saved by then, and saving again
+        // would overwrite the session with an empty one.
+        if let Some(code) = self.exited {
+            *flow = ControlFlow::ExitWithCode(code);
+            return;
+        }
+        if let Event::LoopDestroyed = event {
+            return;
+        }
+        if let Event::NewEvents(_) = event {
+            let now = Instant::now();
+            for window in &mut self.windows {
+                window.redraw_if_due(now);
+            }
+        }
+        let mut handled_windows = vec![];
+        let mut window_control_flow = vec![];
+        let mut windows_to_close = vec![];
+        let mut windows_to_focus = vec![];
+        let mut screenshots_to_save = vec![];
+        let mut exit_code = None;
+        let mut style = None;
+        let mut close_responses = vec![];
+        let mut messages = vec![];
+        while let Some(mut window) = self.windows.pop() {
+            if window.is_event_for_window(&event) {
+                let _span = window.span.clone().entered();
+                // Collect all the other windows.
+                let other_windows = self
+                    .windows
+                    .iter_mut()
+                    .chain(handled_windows.iter_mut())
+                    .map(|container| (container.handle, &mut container.window))
+                    .collect();
+                let mut app = AppCtx::new(
+                    window.handle,
+                    window.repaint.clone(),
+                    other_windows,
+                    &mut self.state,
+                    self.shared_textures.as_mut(),
+                    self.style.as_deref(),
+                );
+                let window_control = match event_loop {
+                    Some(event_loop) => window.handle_event_outer(&event, event_loop, &mut app),
+                    None => Ok(window.handle_headless_event(&event, &mut app)),
+                };
+                let mut window_control = match window_control {
+                    Ok(window_control) => window_control,
+                    Err((e, window_control)) => {
+                        match (self.on_error)(window.handle, &e) {
+                            WindowErrorResponse::Close => windows_to_close.push(window.handle),
+                            WindowErrorResponse::Retry => window.repaint.request_repaint(),
                         }
+                        // What the window asked for on its messages still happens.
+                        window_control
+                    }
+                };
+                windows_to_close.append(&mut app.windows_to_close);
+                windows_to_focus.append(&mut app.windows_to_focus);
+                screenshots_to_save.append(&mut app.screenshots_to_save);
+                exit_code = exit_code.or(app.exit_code);
+                style = app.new_style.take().or(style);
+                close_responses.append(&mut window_control.close_responses);
+                messages.extend(
+                    window_control
+                        .messages
+                        .drain(..)
+                        .map(|(recipient, payload)| (window.handle, recipient, payload)),
+                );
+                match window_control.requested_control_flow {
+                    ControlFlow::Exit => {
+                        // Instead of sending the exit for everyone, just get rid of this one and its children.
+                        tracing::debug!("window requested exit, closing it");
+                        window_control_flow.push(ControlFlow::Exit);
+                        windows_to_close.push(window.handle);
+                        handled_windows.push(window);
+                        continue;
                     }
+                    ControlFlow::ExitWithCode(code) => {
+                        // Any other code is meant for the process, as with `AppCtx::exit`.
+                        tracing::debug!(code, "window requested exit with code");
+                        exit_code = exit_code.or(Some(code));
+                    }
+                    requested_flow => {
+                        window_control_flow.push(requested_flow);
+                    }
+                }
 
+                if !window_control.windows_to_create.is_empty() {
                     for new_window_request in window_control.windows_to_create {
-                        multi_window.add(new_window_request, event_loop_window_target);
-
+                        let result = self.add_running(new_window_request, event_loop);
+                        window.creation_results.push(result);
                     }
+                    // Wake the window up to hand it the outcomes.
+                    window.repaint.request_repaint();
                 }
-                handled_windows.push(window);
-            }
-
-            // Move them back.
-            handled_windows.reverse();
-            multi_window.windows.append(&mut handled_windows);
-
-            // If any window requested polling, we should poll.
-            // Precedence: Poll > WaitUntil(smallest) > Wait.
-            if let ControlFlow::Exit = *flow {
-            } else {
-                *flow = ControlFlow::Wait;
-                for flow_request in window_control_flow {
-                    match flow_request {
-                        ControlFlow::Poll => {
-                            *flow = ControlFlow::Poll;
-                        },
-                        ControlFlow::Wait => (), // do nothing, if untouched it will be wait
-                        ControlFlow::WaitUntil(when_new) => {
-                            if let ControlFlow::Poll = *flow {
-                                continue; // Polling takes precedence, so ignore this.
-                            }
+            }
+            handled_windows.push(window);
+        }
 
-                            // The current flow is already WaitUntil. If this one is sooner, use it instead.
-                            if let ControlFlow::WaitUntil(when_current) = *flow {
-                                if when_new < when_current {
-                                    *flow = ControlFlow::WaitUntil(when_new);
-                                }
-                            }
-                            else { // The current flow is lower precedence, so replace it with this.
+        // Move them back.
+        handled_windows.reverse();
+        self.windows.append(&mut handled_windows);
+
+        if let Some(style) = style {
+            self.set_style(style);
+        }
+
+        for (from, recipient, payload) in messages {
+            self.deliver(from, recipient, payload);
+        }
+
+        // The session is taken before this event closes any window, see `remember_session`.
+        let session = match &self.session {
+            Some(saver) if !windows_to_close.is_empty() || !close_responses.is_empty() => {
+                Some((saver.to_ron)(&self.windows))
+            }
+            _ => None,
+        };
+
+        for (handle, response) in close_responses {
+            self.resolve_close(handle, response);
+        }
+        for handle in windows_to_close {
+            self.close(handle);
+        }
+        for handle in windows_to_focus {
+            self.focus(handle);
+        }
+        for (handle, path) in screenshots_to_save {
+            if let Err(e) = self.save_screenshot(handle, &path) {
+                tracing::warn!(handle = handle.0, path = %path.display(), error = %e, "couldn't save screenshot");
+            }
+        }
+
+        // Windows waiting for a delayed repaint keep the loop from sleeping past their deadline,
+        // even if this event wasn't one of theirs.
+        window_control_flow.extend(
+            self.windows
+                .iter()
+                .filter_map(|window| window.repaint_deadline)
+                .map(ControlFlow::WaitUntil),
+        );
+
+        // If any window requested polling, we should poll.
+        // Precedence: Poll > WaitUntil(smallest) > Wait.
+        if let ControlFlow::ExitWithCode(_) = *flow {
+        } else {
+            *flow = ControlFlow::Wait;
+            for flow_request in window_control_flow {
+                match flow_request {
+                    ControlFlow::Poll => {
+                        *flow = ControlFlow::Poll;
+                    }
+                    ControlFlow::Wait => (), // do nothing, if untouched it will be wait
+                    ControlFlow::WaitUntil(when_new) => {
+                        if let ControlFlow::Poll = *flow {
+                            continue; // Polling takes precedence, so ignore this.
+                        }
+
+                        // The current flow is already WaitUntil. If this one is sooner, use it instead.
+                        if let ControlFlow::WaitUntil(when_current) = *flow {
+                            if when_new < when_current {
                                 *flow = ControlFlow::WaitUntil(when_new);
                             }
-                        },
-                        ControlFlow::ExitWithCode(n) => (),
-                        ControlFlow::Exit => (), // handle differently, only exit if all windows are gone?? what do about a closed root window
+                        } else {
+                            // The current flow is lower precedence, so replace it with this.
+                            *flow = ControlFlow::WaitUntil(when_new);
+                        }
                     }
+                    // Windows asking to exit were handled above.
+                    ControlFlow::ExitWithCode(_) => (),
                 }
             }
+        }
 
-            // for window in &mut self.windows {
-            //     if window.is_event_for_window(&event){
-            //         match window.handle_event_outer(&event, vec![]) {
-            //             Some(ControlFlow::Exit) => {
-            //                 *flow = ControlFlow::Exit
-            //             },
-            //             Some(_) => (),
-            //             None => ()
-            //         }
-            //     }
-            // }
-
-
-            // for option in &mut self.windows {
-            //     if let Some(window) = option.as_mut() {
-            //         match window.handle_event_outer(&event) {
-            //             Some(ControlFlow::Exit) => {
-            //                 *flow = ControlFlow::Exit
-            //             },
-            //             Some(_) => (),
-            //             None => ()
-            //         }
-            //     }
-            // }
-
-            if multi_window.windows.is_empty() {
-                println!("no more windows running, exiting event loop.");
-                *flow = ControlFlow::Exit;
+        let exit = exit_code
+            .or_else(|| self.requested_exit.take())
+            .map(|code| (code, "exit requested"))
+            .or_else(|| match self.exit_policy {
+                ExitPolicy::LastWindowClosed if self.windows.is_empty() => {
+                    Some((0, "no more windows running"))
+                }
+                ExitPolicy::PrimaryWindowClosed(primary) if self.get(primary).is_none() => {
+                    Some((0, "primary window closed"))
+                }
+                _ => None,
+            });
+        if let Some((code, reason)) = exit {
+            tracing::info!(code, reason, "exiting event loop");
+            // Nothing was closed during this event, so the windows still open are the session.
+            let session = session.or_else(|| {
+                self.session
+                    .as_ref()
+                    .map(|saver| (saver.to_ron)(&self.windows))
+            });
+            // Close whatever is left so every window's GL resources are released.
+            while let Some(window) = self.windows.last() {
+                self.close(window.handle);
+            }
+            if let Err(e) = self.save_geometry() {
+                tracing::warn!(error = %e, "couldn't save window geometry");
+            }
+            #[cfg(feature = "persistence")]
+            if let Err(e) = self.save_egui_memory() {
+                tracing::warn!(error = %e, "couldn't save egui memory");
+            }
+            if let (Some(saver), Some(session)) = (&self.session, session) {
+                let saved = session.and_then(|ron| Ok(std::fs::write(&saver.path, ron)?));
+                if let Err(e) = saved {
+                    tracing::warn!(error = %e, "couldn't save the session");
+                }
+            }
+            self.exited = Some(code);
+            *flow = ControlFlow::ExitWithCode(code);
+        }
+    }
+}
 
+impl<W, T, S, M> MultiWindow<W, T, S, M> {
+    /// Destroys the painters of the closed windows that drew shared textures. They delete the
+    /// shared textures too, so this is only done once no open window draws them, and the next
+    /// window that is drawn uploads them again. Their objects belong to the share group, so the
+    /// hidden context is made current to delete them.
+    fn destroy_retired_painters(&mut self) {
+        let context = match self.shared_context.take() {
+            Some(context) if !self.retired_painters.is_empty() => context,
+            context => {
+                self.shared_context = context;
+                return;
             }
-        });
+        };
+        match unsafe { context.make_current() } {
+            Ok(context) => {
+                for mut painter in self.retired_painters.drain(..) {
+                    painter.destroy();
+                }
+                if let Some(shared_textures) = &mut self.shared_textures {
+                    shared_textures.forget_uploads();
+                }
+                self.shared_context = Some(unsafe { context.treat_as_not_current() });
+            }
+            Err((context, e)) => {
+                // The objects go away along with the contexts.
+                tracing::warn!(error = %e, "couldn't destroy the painters of closed windows");
+                for painter in self.retired_painters.drain(..) {
+                    mem::forget(painter);
+                }
+                self.shared_context = Some(context);
+            }
+        }
     }
 }
 
-pub struct NewWindowRequest {
-    pub window_state: MyWindows,
+/// Applies the geometry remembered for a keyed window to its builder.
+fn restore_geometry<W, TE>(
+    geometry: &GeometryStore,
+    mut request: NewWindowRequest<W>,
+    event_loop: &EventLoopWindowTarget<TE>,
+) -> NewWindowRequest<W> {
+    if let Some(saved) = request.key.as_deref().and_then(|key| geometry.get(key)) {
+        request.builder = saved.apply(request.builder, event_loop);
+    }
+    request
+}
+
+/// Remembers the current geometry of a keyed window. A window that has been maximized all along
+/// falls back on what was remembered for it in the last run.
+fn record_geometry<W, M>(geometry: &mut GeometryStore, container: &TrackedWindowContainer<W, M>) {
+    if let (Some(key), Some(window)) = (&container.key, container.gl_window.window()) {
+        let restored = container
+            .restored_geometry
+            .as_ref()
+            .or_else(|| geometry.get(key));
+        if let Some(remembered) = WindowGeometry::remembered(window, restored) {
+            geometry.insert(key.clone(), remembered);
+        }
+    }
+}
+
+/// Remembers the current egui memory of a keyed window.
+#[cfg(feature = "persistence")]
+fn record_egui_memory<W, M>(
+    egui_memory: &mut EguiMemoryStore,
+    container: &TrackedWindowContainer<W, M>,
+) {
+    if let (Some(key), Some(ctx)) = (&container.key, container.egui_ctx()) {
+        egui_memory.insert(key.clone(), ctx.memory().clone());
+    }
+}
+
+/// The default `MultiWindow::on_error`.
+fn close_on_error(handle: WindowHandle, error: &WindowError) -> WindowErrorResponse {
+    tracing::error!(handle = handle.0, %error, "closing window after an error");
+    WindowErrorResponse::Close
+}
+
+/// A window to be created and added to a `MultiWindow`.
+pub struct NewWindowRequest<W> {
+    pub window_state: W,
     pub builder: glutin::window::WindowBuilder,
+    /// The window that owns this one. When the parent closes, this window is closed too.
+    pub parent: Option<WindowHandle>,
+    /// The attributes of the window's GL context.
+    pub gl_config: GlConfig,
+    /// Identifies the window across runs, see `with_key`.
+    pub key: Option<String>,
+}
+
+impl<W> NewWindowRequest<W> {
+    /// Requests a top-level window with no parent.
+    pub fn new(window_state: W, builder: glutin::window::WindowBuilder) -> Self {
+        NewWindowRequest {
+            window_state,
+            builder,
+            parent: None,
+            gl_config: GlConfig::default(),
+            key: None,
+        }
+    }
+
+    /// Makes the window a child of `parent`, so that it is closed along with it.
+    pub fn with_parent(mut self, parent: WindowHandle) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Gives the window a key that stays the same from one run to the next, such as
+    /// `"palette/colors"`, so it can be remembered across runs, see `MultiWindow::remember_geometry`.
+    /// No two open windows should have the same key.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Creates the window's GL context with `gl_config` instead of the default attributes.
+    pub fn with_gl_config(mut self, gl_config: GlConfig) -> Self {
+        self.gl_config = gl_config;
+        self
+    }
+}
+
+/// The attributes of a window's GL context. The default suits windows that only show egui: no
+/// depth or stencil buffer, no multisampling, vsync on and the latest GL version available. The
+/// context is always sRGB, which egui's colors rely on. Headless windows only check that it is
+/// valid.
+#[derive(Clone, Copy, Debug)]
+pub struct GlConfig {
+    /// Bits per pixel of the depth buffer, 0 for none.
+    pub depth_bits: u8,
+    /// Bits per pixel of the stencil buffer, 0 for none.
+    pub stencil_bits: u8,
+    /// Samples per pixel for multisample anti-aliasing, 0 to turn it off. Must be a power of two,
+    /// or creating the window fails with `DisplayCreationError::Multisampling`.
+    pub multisampling: u16,
+    pub vsync: bool,
+    /// Which GL or GLES version to ask for. egui needs at least GL 2 or GLES 2.
+    pub version: glutin::GlRequest,
+    /// Core or compatibility profile, if the platform should be told which.
+    pub profile: Option<glutin::GlProfile>,
+    /// Asks for a debug context, which reports more errors at some cost in speed.
+    pub debug: bool,
+}
+
+impl GlConfig {
+    /// Fails on attributes that glutin would panic on.
+    pub(crate) fn check(&self) -> Result<(), DisplayCreationError> {
+        if self.multisampling != 0 && !self.multisampling.is_power_of_two() {
+            return Err(DisplayCreationError::Multisampling(self.multisampling));
+        }
+        Ok(())
+    }
+}
+
+impl Default for GlConfig {
+    fn default() -> Self {
+        GlConfig {
+            depth_bits: 0,
+            stencil_bits: 0,
+            multisampling: 0,
+            vsync: true,
+            version: glutin::GlRequest::Latest,
+            profile: None,
+            debug: false,
+        }
+    }
 }
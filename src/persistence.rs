@@ -0,0 +1,224 @@
+//! Remembering windows across runs.
+//!
+//! Windows are remembered by the key given to `NewWindowRequest::with_key`, so a window created
+//! with the same key in a later run can pick up where the last one left off. Windows without a key
+//! are never remembered.
+//!
+//! `MultiWindow::remember_geometry` loads a file of window positions and sizes, applies them to
+//! windows created with a known key, and writes the geometry of every keyed window back to the file
+//! when the event loop exits. With the `persistence` feature, `MultiWindow::remember_egui_memory`
+//! does the same for each window's `egui::Memory`: collapsed headers, scroll positions, panel sizes
+//! and where egui windows were moved to.
+//!
+//! Whole sessions can be remembered too: `MultiWindow::remember_session` writes out which windows
+//! were open and which window owned which when the event loop exits, and
+//! `MultiWindow::restore_session` reopens them on the next launch. The window type decides what is
+//! saved about each window by implementing `SessionState`.
+
+use std::{collections::BTreeMap, path::Path};
+
+use glutin::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::EventLoopWindowTarget,
+    window::{Window, WindowBuilder},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    multi_window::NewWindowRequest,
+    tracked_window::{TrackedWindowContainer, WindowHandle},
+};
+
+/// Where a window was and how big it was, in physical pixels.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// The position of the window's top left corner, including decorations, on the desktop. `None`
+    /// on platforms that don't tell windows where they are, such as Wayland.
+    pub position: Option<(i32, i32)>,
+    /// The size of the window's client area.
+    pub size: (u32, u32),
+    pub maximized: bool,
+    /// The name of the monitor the window was mostly on.
+    pub monitor: Option<String>,
+}
+
+impl WindowGeometry {
+    /// The current geometry of a window.
+    pub fn of(window: &Window) -> Self {
+        let size = window.inner_size();
+        WindowGeometry {
+            position: window
+                .outer_position()
+                .ok()
+                .map(|position| (position.x, position.y)),
+            size: (size.width, size.height),
+            maximized: window.is_maximized(),
+            monitor: window.current_monitor().and_then(|monitor| monitor.name()),
+        }
+    }
+
+    /// The geometry to remember for a window. A maximized window is remembered as maximized with
+    /// the size and position it had when it was last not maximized, `restored`, so it doesn't fill
+    /// the screen once it is un-maximized in the next run. `None` if the window is maximized and
+    /// where it was before isn't known.
+    pub(crate) fn remembered(
+        window: &Window,
+        restored: Option<&WindowGeometry>,
+    ) -> Option<WindowGeometry> {
+        let current = WindowGeometry::of(window);
+        if !current.maximized {
+            return Some(current);
+        }
+        restored.map(|restored| WindowGeometry {
+            position: restored.position,
+            size: restored.size,
+            maximized: true,
+            monitor: restored.monitor.clone(),
+        })
+    }
+
+    /// Sets up a window to be created with this geometry. The position is left to the OS if the
+    /// window's monitor has been unplugged since, so the window doesn't end up off screen.
+    pub fn apply<TE>(
+        &self,
+        builder: WindowBuilder,
+        event_loop: &EventLoopWindowTarget<TE>,
+    ) -> WindowBuilder {
+        let builder = builder
+            .with_inner_size(PhysicalSize::new(self.size.0, self.size.1))
+            .with_maximized(self.maximized);
+        let monitor_present = match &self.monitor {
+            Some(name) => event_loop
+                .available_monitors()
+                .any(|monitor| monitor.name().as_ref() == Some(name)),
+            None => true,
+        };
+        match self.position {
+            Some((x, y)) if monitor_present => builder.with_position(PhysicalPosition::new(x, y)),
+            _ => builder,
+        }
+    }
+}
+
+/// Something remembered about every keyed window, by key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyedStore<V> {
+    windows: BTreeMap<String, V>,
+}
+
+/// The geometry of every window that was remembered.
+pub type GeometryStore = KeyedStore<WindowGeometry>;
+
+/// The egui memory of every window that was remembered.
+#[cfg(feature = "persistence")]
+pub type EguiMemoryStore = KeyedStore<egui::Memory>;
+
+impl<V> Default for KeyedStore<V> {
+    fn default() -> Self {
+        KeyedStore {
+            windows: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Serialize + for<'de> Deserialize<'de>> KeyedStore<V> {
+    /// Reads a store written by `save`. A missing file is an empty store.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PersistenceError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(KeyedStore::default());
+        }
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the store to `path` as RON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistenceError> {
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+}
+
+impl<V> KeyedStore<V> {
+    /// What is remembered for the window with this key.
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.windows.get(key)
+    }
+
+    /// Remembers something about the window with this key, replacing what was remembered before.
+    pub fn insert(&mut self, key: impl Into<String>, value: V) {
+        self.windows.insert(key.into(), value);
+    }
+
+    /// Forgets the window with this key, so it starts from scratch next time.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        self.windows.remove(key)
+    }
+}
+
+/// A window type whose windows can be saved at exit and reopened on the next launch, see
+/// `MultiWindow::remember_session`. Usually implemented by the application's window enum.
+pub trait SessionState: Sized {
+    /// What is saved about a window, e.g. the text typed into it.
+    type Saved: Serialize + DeserializeOwned;
+
+    /// What to save about this window, or `None` if it shouldn't be reopened. Windows owned by a
+    /// window that isn't reopened aren't reopened either.
+    fn save_session(&self) -> Option<Self::Saved>;
+
+    /// Recreates a window from what `save_session` returned, or returns `None` to leave it closed,
+    /// e.g. when it can't be reopened without its parent. `parent` is the reopened window that
+    /// owned it, if any; the returned request is made a child of it. Windows owned by a window
+    /// that isn't reopened aren't reopened either.
+    fn restore_session(
+        saved: Self::Saved,
+        parent: Option<WindowHandle>,
+    ) -> Option<NewWindowRequest<Self>>;
+}
+
+/// The windows that were open at the end of a run.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Session<V> {
+    /// In the order they were created, so parents come before their children.
+    pub(crate) windows: Vec<SessionWindow<V>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionWindow<V> {
+    /// The window's handle in the run that saved it, only meaningful within the session.
+    pub(crate) handle: u64,
+    pub(crate) parent: Option<u64>,
+    pub(crate) state: V,
+}
+
+/// Saves the windows that are open as a session, in RON.
+pub(crate) fn session_to_ron<W: SessionState, M>(
+    windows: &[TrackedWindowContainer<W, M>],
+) -> Result<String, PersistenceError> {
+    let mut windows: Vec<_> = windows
+        .iter()
+        .filter_map(|container| {
+            Some(SessionWindow {
+                handle: container.handle.0,
+                parent: container.parent.map(|parent| parent.0),
+                state: container.window.save_session()?,
+            })
+        })
+        .collect();
+    windows.sort_by_key(|window| window.handle);
+    Ok(ron::ser::to_string_pretty(
+        &Session { windows },
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+#[derive(Error, Debug)]
+pub enum PersistenceError {
+    #[error("couldn't read or write the file {0}")]
+    Io(#[from] std::io::Error),
+    #[error("couldn't parse the file {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("couldn't serialize {0}")]
+    Serialize(#[from] ron::Error),
+}
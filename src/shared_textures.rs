@@ -0,0 +1,105 @@
+//! Textures uploaded once and drawn by every window.
+//!
+//! After `MultiWindow::share_gl_context`, the GL contexts of the windows share their textures, so a
+//! texture uploaded by one window can be drawn by all of them. `SharedTextures` hands out
+//! `egui::TextureId`s for such textures: load an image once, through
+//! `MultiWindow::shared_textures` or `AppCtx::shared_textures`, and use the id in any window, e.g.
+//! with `ui.image`. The image is uploaded by the next window that is drawn and then registered
+//! with every other window's painter as it draws.
+//!
+//! The font atlas isn't shared, and is uploaded by every window. Each window's egui context adds
+//! glyphs to an atlas of its own as it first draws them, so even windows with the same fonts and
+//! pixels per point end up with atlases laid out differently, and egui has no way to give several
+//! contexts one atlas.
+
+use egui::{epaint::ImageDelta, TextureId};
+use egui_glow::Painter;
+
+use crate::headless::HeadlessWindow;
+
+/// Shared textures have ids from here on, well clear of the ids painters give native textures
+/// registered with them.
+const FIRST_ID: u64 = 1 << 63;
+
+/// The textures every window of a `MultiWindow` can draw. They last as long as the `MultiWindow`.
+pub struct SharedTextures {
+    /// A texture's index is its id, counted from `FIRST_ID`.
+    textures: Vec<SharedTexture>,
+}
+
+struct SharedTexture {
+    /// Kept after the upload, so the texture can be uploaded again once the painters of closed
+    /// windows have deleted it, see `forget_uploads`.
+    image: ImageDelta,
+    /// `None` until a window uploads it. Headless windows never upload textures.
+    native: Option<glow::Texture>,
+}
+
+impl SharedTextures {
+    pub(crate) fn new() -> Self {
+        SharedTextures { textures: vec![] }
+    }
+
+    /// Adds a texture that every window can draw, returning its id.
+    pub fn load(
+        &mut self,
+        image: impl Into<egui::ImageData>,
+        filter: egui::TextureFilter,
+    ) -> TextureId {
+        let id = texture_id(self.textures.len());
+        self.textures.push(SharedTexture {
+            image: ImageDelta::full(image, filter),
+            native: None,
+        });
+        id
+    }
+
+    /// Whether `id` was handed out by `load`.
+    pub fn contains(&self, id: TextureId) -> bool {
+        match id {
+            TextureId::User(id) => id >= FIRST_ID && id - FIRST_ID < self.textures.len() as u64,
+            TextureId::Managed(_) => false,
+        }
+    }
+
+    /// Uploads the textures no window has uploaded yet with `painter`, and registers the others
+    /// `painter` doesn't know yet with it. `registered` is how many textures `painter` knows, and
+    /// is updated. The painter's context must be current.
+    pub(crate) fn register(&mut self, painter: &mut Painter, registered: &mut usize) {
+        for (index, texture) in self.textures.iter_mut().enumerate().skip(*registered) {
+            let id = texture_id(index);
+            match texture.native {
+                Some(native) => painter.replace_native_texture(id, native),
+                None => {
+                    // Uploading through the painter registers the texture with it too.
+                    painter.set_texture(id, &texture.image);
+                    let native = painter
+                        .texture(id)
+                        .expect("the painter just created the texture");
+                    texture.native = Some(native);
+                }
+            }
+        }
+        *registered = self.textures.len();
+    }
+
+    /// Copies the textures a headless window doesn't have yet into it, for its screenshots.
+    pub(crate) fn register_headless(&self, headless: &mut HeadlessWindow, registered: &mut usize) {
+        for (index, texture) in self.textures.iter().enumerate().skip(*registered) {
+            headless.set_texture(texture_id(index), &texture.image);
+        }
+        *registered = self.textures.len();
+    }
+
+    /// Has the next window that is drawn upload every texture again, once the painters that
+    /// deleted them have been destroyed and no open window draws them.
+    pub(crate) fn forget_uploads(&mut self) {
+        for texture in &mut self.textures {
+            texture.native = None;
+        }
+    }
+}
+
+fn texture_id(index: usize) -> TextureId {
+    TextureId::User(FIRST_ID + index as u64)
+}
@@ -0,0 +1,166 @@
+//! Golden-image tests for windows of a headless `MultiWindow`.
+//!
+//! `assert_window_snapshot!(multi_window, handle, "name")` renders the window's last frame and
+//! compares it with `tests/snapshots/name.png` in the crate being tested. When they differ, the new
+//! rendering is written next to it as `name.new.png`, along with `name.diff.png` highlighting the
+//! pixels that changed. Running the tests with the `UPDATE_SNAPSHOTS` environment variable set
+//! overwrites the stored snapshots with the new renderings instead.
+
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+use thiserror::Error;
+
+use crate::{
+    multi_window::MultiWindow,
+    tracked_window::{ScreenshotError, WindowHandle},
+};
+
+/// Set to anything to store new snapshots instead of comparing against the old ones.
+pub const UPDATE_ENV_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// How closely a rendering has to match its snapshot. The default only accepts an exact match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// How far any channel of a pixel may be from the snapshot before the pixel counts as
+    /// different.
+    pub tolerance: u8,
+    /// How many pixels may be different before the check fails.
+    pub max_different_pixels: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("couldn't render the window {0}")]
+    Screenshot(#[from] ScreenshotError),
+    #[error("couldn't read or write a snapshot {0}")]
+    Image(#[from] image::ImageError),
+    #[error("couldn't create the snapshot directory {0}")]
+    Io(#[from] std::io::Error),
+    #[error("there is no snapshot {}, the rendering was written to {}; set {} to store it", .snapshot.display(), .new.display(), UPDATE_ENV_VAR)]
+    Missing { snapshot: PathBuf, new: PathBuf },
+    #[error("the rendering is {actual:?} pixels but the snapshot is {expected:?}, see {}", .new.display())]
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+        new: PathBuf,
+    },
+    #[error("{different_pixels} pixels differ from the snapshot, see {} and {}", .new.display(), .diff.display())]
+    Mismatch {
+        different_pixels: usize,
+        new: PathBuf,
+        diff: PathBuf,
+    },
+}
+
+/// Renders a window of a headless `MultiWindow` and checks it against the snapshot called `name`
+/// in `directory`. Prefer `assert_window_snapshot!`, which picks the directory and panics with the
+/// error.
+pub fn check_window_snapshot<W, T: Send, S, M>(
+    multi_window: &mut MultiWindow<W, T, S, M>,
+    handle: WindowHandle,
+    directory: impl AsRef<Path>,
+    name: &str,
+    options: &SnapshotOptions,
+) -> Result<(), SnapshotError> {
+    let image = multi_window.screenshot(handle)?;
+    check_snapshot(&image, directory, name, options)
+}
+
+/// Checks an image against the snapshot called `name` in `directory`, or stores it as the new
+/// snapshot if `UPDATE_SNAPSHOTS` is set.
+pub fn check_snapshot(
+    image: &RgbaImage,
+    directory: impl AsRef<Path>,
+    name: &str,
+    options: &SnapshotOptions,
+) -> Result<(), SnapshotError> {
+    let directory = directory.as_ref();
+    let snapshot_path = directory.join(format!("{}.png", name));
+    let new_path = directory.join(format!("{}.new.png", name));
+    let diff_path = directory.join(format!("{}.diff.png", name));
+    std::fs::create_dir_all(directory)?;
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        image.save(&snapshot_path)?;
+        return Ok(());
+    }
+    if !snapshot_path.exists() {
+        image.save(&new_path)?;
+        return Err(SnapshotError::Missing {
+            snapshot: snapshot_path,
+            new: new_path,
+        });
+    }
+
+    let snapshot = image::open(&snapshot_path)?.into_rgba8();
+    if snapshot.dimensions() != image.dimensions() {
+        image.save(&new_path)?;
+        return Err(SnapshotError::SizeMismatch {
+            expected: snapshot.dimensions(),
+            actual: image.dimensions(),
+            new: new_path,
+        });
+    }
+
+    // Unchanged pixels are dimmed, so the changed ones in red stand out.
+    let mut diff = RgbaImage::new(image.width(), image.height());
+    let mut different_pixels = 0;
+    for ((new, old), diff) in image.pixels().zip(snapshot.pixels()).zip(diff.pixels_mut()) {
+        let differs = new
+            .0
+            .iter()
+            .zip(old.0)
+            .any(|(new, old)| new.abs_diff(old) > options.tolerance);
+        *diff = if differs {
+            different_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let [r, g, b, _] = old.0;
+            Rgba([r / 3, g / 3, b / 3, 255])
+        };
+    }
+
+    if different_pixels > options.max_different_pixels {
+        image.save(&new_path)?;
+        diff.save(&diff_path)?;
+        return Err(SnapshotError::Mismatch {
+            different_pixels,
+            new: new_path,
+            diff: diff_path,
+        });
+    }
+    // Leftovers from an earlier failure would only be confusing now.
+    for leftover in [new_path, diff_path] {
+        if leftover.exists() {
+            std::fs::remove_file(leftover)?;
+        }
+    }
+    Ok(())
+}
+
+/// Asserts that a window of a headless `MultiWindow` looks like its stored snapshot. Snapshots
+/// live in `tests/snapshots` of the crate using the macro. Takes an optional `SnapshotOptions`
+/// after the name, e.g. `assert_window_snapshot!(multi_window, popup, "popup", options)`.
+#[macro_export]
+macro_rules! assert_window_snapshot {
+    ($multi_window:expr, $handle:expr, $name:expr) => {
+        $crate::assert_window_snapshot!(
+            $multi_window,
+            $handle,
+            $name,
+            $crate::snapshot::SnapshotOptions::default()
+        )
+    };
+    ($multi_window:expr, $handle:expr, $name:expr, $options:expr) => {
+        if let Err(e) = $crate::snapshot::check_window_snapshot(
+            &mut $multi_window,
+            $handle,
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots"),
+            $name,
+            &$options,
+        ) {
+            panic!("snapshot {:?} doesn't match: {}", $name, e);
+        }
+    };
+}
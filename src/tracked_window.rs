@@ -1,12 +1,24 @@
-use std::{mem, sync::Arc};
+use std::{
+    mem,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
 use crate::{
-    multi_window::{MultiWindow, NewWindowRequest},
-    windows::MyWindows,
+    app_ctx::AppCtx,
+    app_style::AppStyle,
+    headless::HeadlessWindow,
+    message::{Message, Recipient},
+    multi_window::NewWindowRequest,
+    persistence::WindowGeometry,
 };
 use egui_glow::EguiGlow;
 use glutin::{
-    event::Event,
+    event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoopWindowTarget},
     PossiblyCurrent,
 };
@@ -14,32 +26,186 @@ use thiserror::Error;
 
 /// A window being tracked by a `MultiWindow`. All tracked windows will be forwarded all events
 /// received on the `MultiWindow`'s event loop.
-#[enum_dispatch]
-pub trait TrackedWindow {
-    /// Handles one event from the event loop. Returns true if the window needs to be kept alive,
-    /// otherwise it will be closed. Window events should be checked to ensure that their ID is one
-    /// that the TrackedWindow is interested in.
+///
+/// `W` is the application's window type that every tracked window is stored as. It is usually an
+/// enum wrapping each kind of window, which implements this trait by forwarding to the variants.
+/// `T` is the user event type of the `MultiWindow`'s event loop, `S` is the application state
+/// shared by every window, and `M` is the type of messages windows send each other.
+pub trait TrackedWindow<W, T = (), S = (), M = ()> {
+    /// Builds the window's UI for one frame. Taking egui's input, painting the output and
+    /// scheduling the next frame are all done by the `TrackedWindowContainer`.
+    fn update(
+        &mut self,
+        _ctx: &egui::Context,
+        _app: &mut AppCtx<W, S>,
+    ) -> TrackedWindowControl<W, M> {
+        TrackedWindowControl::default()
+    }
+
+    /// Low-level access to every event this window receives, for windows that need to do more
+    /// than `update` allows, such as drawing with GL directly. Returns `None` to let the
+    /// container handle the event with its default frame loop, which calls `update`. Returning a
+    /// `TrackedWindowControl` means the window has fully handled the event itself, including
+    /// painting and swapping buffers for redraws. Errors are passed to `MultiWindow::on_error`.
     fn handle_event(
         &mut self,
-        event: &glutin::event::Event<()>,
-        other_windows: Vec<&mut crate::windows::MyWindows>,
-        egui: &mut EguiGlow,
-        gl_window: &mut glutin::WindowedContext<PossiblyCurrent>,
-    ) -> TrackedWindowControl;
+        _event: &glutin::event::Event<T>,
+        _app: &mut AppCtx<W, S>,
+        _egui: &mut EguiGlow,
+        _gl_window: &mut glutin::WindowedContext<PossiblyCurrent>,
+    ) -> Result<Option<TrackedWindowControl<W, M>>, WindowError> {
+        Ok(None)
+    }
+
+    /// Called with each message sent to this window, before the event that woke the window up is
+    /// passed to `handle_event`. Only the windows, close responses and messages in the returned
+    /// control are used, and its control flow if it asks to exit.
+    fn on_message(
+        &mut self,
+        _message: Message<M>,
+        _app: &mut AppCtx<W, S>,
+    ) -> TrackedWindowControl<W, M> {
+        TrackedWindowControl::default()
+    }
+
+    /// Called with each user event sent through a `MultiWindowProxy`, before the event is passed
+    /// to `handle_event`. Headless windows get user events only through this. Only the windows,
+    /// close responses and messages in the returned control are used, and its control flow if it
+    /// asks to exit.
+    fn on_user_event(&mut self, _event: &T, _app: &mut AppCtx<W, S>) -> TrackedWindowControl<W, M> {
+        TrackedWindowControl::default()
+    }
+
+    /// Called with the outcome of each window this window asked for through
+    /// `TrackedWindowControl::windows_to_create`, in the order they were asked for. Like messages,
+    /// outcomes are handed over before the window's next event. By default errors are logged.
+    fn on_window_created(
+        &mut self,
+        result: Result<WindowHandle, DisplayCreationError>,
+        _app: &mut AppCtx<W, S>,
+    ) -> TrackedWindowControl<W, M> {
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "couldn't create requested window");
+        }
+        TrackedWindowControl::default()
+    }
+
+    /// Called when this window's parent has closed, just before this window is closed as well.
+    fn on_parent_closed(&mut self, _parent: WindowHandle) {}
+
+    /// Called when the user asks to close this window, before the `CloseRequested` event is passed
+    /// to `handle_event`, or when a test calls `HeadlessWindow::request_close`. A deferred request
+    /// stays pending until it is answered through `TrackedWindowControl::close_responses`, either
+    /// by this window or by another one such as a confirmation dialog.
+    fn on_close_requested(&mut self, _app: &mut AppCtx<W, S>) -> CloseRequestResponse {
+        CloseRequestResponse::Allow
+    }
+}
+
+/// How a window answers a request to close it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseRequestResponse {
+    /// Close the window.
+    Allow,
+    /// Keep the window open.
+    Deny,
+    /// Keep the window open until the request is answered with `Allow` or `Deny`.
+    Defer,
+}
+
+/// Identifies a window tracked by a `MultiWindow`. Handles are never reused, so a handle to a
+/// closed window will simply not be found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WindowHandle(pub(crate) u64);
+
+/// Asks for one window to be repainted from any thread. Requests wake up the event loop and only
+/// redraw that window. A window's egui context uses its handle for `Context::request_repaint`, so
+/// a clone of the context works just as well.
+#[derive(Clone)]
+pub struct RepaintHandle {
+    window: WindowHandle,
+    pub(crate) requested: Arc<AtomicBool>,
+    wake: Arc<dyn Fn(WindowHandle) + Send + Sync>,
 }
 
-pub struct TrackedWindowContainer {
+impl RepaintHandle {
+    pub(crate) fn new(window: WindowHandle, wake: Arc<dyn Fn(WindowHandle) + Send + Sync>) -> Self {
+        RepaintHandle {
+            window,
+            requested: Arc::new(AtomicBool::new(false)),
+            wake,
+        }
+    }
+
+    /// The window this handle repaints.
+    pub fn window(&self) -> WindowHandle {
+        self.window
+    }
+
+    /// Repaints the window as soon as the event loop gets to it.
+    pub fn request_repaint(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        (self.wake)(self.window);
+    }
+}
+
+pub struct TrackedWindowContainer<W, M = ()> {
+    pub handle: WindowHandle,
+    pub parent: Option<WindowHandle>,
     pub gl_window: IndeterminateWindowedContext,
     pub egui: Option<EguiGlow>,
-    pub window: MyWindows,
+    pub window: W,
+    /// Set while a close request for this window has been deferred.
+    pub(crate) close_pending: bool,
+    /// When egui asked for this window to be repainted next, if it asked for a delayed repaint.
+    pub(crate) repaint_deadline: Option<Instant>,
+    /// Also used as egui's repaint callback, so it sees every `Context::request_repaint`.
+    pub(crate) repaint: RepaintHandle,
+    /// Messages sent to this window that it hasn't been given yet.
+    pub(crate) mailbox: Vec<Message<M>>,
+    /// Outcomes of the windows this window asked for that it hasn't been given yet.
+    pub(crate) creation_results: Vec<Result<WindowHandle, DisplayCreationError>>,
+    /// Only set for windows painted by the default frame loop.
+    last_frame: Option<LastFrame>,
+    /// Set when winit asks for this window to be redrawn, until the default frame loop runs the
+    /// frame. Only used on Windows, see `default_frame_loop`.
+    redraw_pending: bool,
+    /// How many of the `SharedTextures` the window's painter knows, or `None` if the window's GL
+    /// context isn't shared with the other windows.
+    shared_textures_registered: Option<usize>,
+    /// The window's painter once the window is destroyed, if it drew shared textures. Destroying
+    /// the painter would delete them, so the `MultiWindow` keeps it until no open window draws
+    /// them.
+    pub(crate) retired_painter: Option<egui_glow::Painter>,
+    /// Identifies the window across runs, see `NewWindowRequest::with_key`.
+    pub(crate) key: Option<String>,
+    /// The geometry of a keyed window when it was last not maximized, see
+    /// `WindowGeometry::remembered`.
+    pub(crate) restored_geometry: Option<WindowGeometry>,
+    /// Remembered egui memory, waiting for the window's egui context to be created.
+    #[cfg(feature = "persistence")]
+    pending_egui_memory: Option<egui::Memory>,
+    /// The style of the `MultiWindow`, if it was given one.
+    style: Option<Arc<AppStyle>>,
+    /// Entered while the window handles an event, so everything logged meanwhile says which window
+    /// it is about.
+    pub(crate) span: tracing::Span,
+}
+
+/// What a window painted in its last frame, kept so it can be painted again for a screenshot.
+struct LastFrame {
+    primitives: Vec<egui::ClippedPrimitive>,
+    pixels_per_point: f32,
 }
 
-impl TrackedWindowContainer {
+impl<W, M> TrackedWindowContainer<W, M> {
     pub fn create<TE>(
-        window: MyWindows,
-        window_builder: glutin::window::WindowBuilder,
+        handle: WindowHandle,
+        request: NewWindowRequest<W>,
         event_loop: &glutin::event_loop::EventLoopWindowTarget<TE>,
-    ) -> Result<TrackedWindowContainer, DisplayCreationError> {
+        repaint: RepaintHandle,
+        shared_context: Option<&glutin::Context<glutin::NotCurrent>>,
+    ) -> Result<TrackedWindowContainer<W, M>, DisplayCreationError> {
         // let window_builder = glutin::window::WindowBuilder::new()
         //     .with_resizable(true)
         //     .with_inner_size(glutin::dpi::LogicalSize {
@@ -48,130 +214,694 @@ impl TrackedWindowContainer {
         //     })
         //     .with_title("egui_glow example");
 
-        let gl_window = glutin::ContextBuilder::new()
-            .with_depth_buffer(0)
+        let span = window_span(handle, request.parent);
+        let config = request.gl_config;
+        config.check()?;
+        let mut context = glutin::ContextBuilder::new()
+            .with_depth_buffer(config.depth_bits)
             .with_srgb(true)
-            .with_stencil_buffer(0)
-            .with_vsync(true)
-            .build_windowed(window_builder, event_loop)?;
+            .with_stencil_buffer(config.stencil_bits)
+            .with_multisampling(config.multisampling)
+            .with_vsync(config.vsync)
+            .with_gl(config.version)
+            .with_gl_debug_flag(config.debug);
+        if let Some(profile) = config.profile {
+            context = context.with_gl_profile(profile);
+        }
+        if let Some(shared_context) = shared_context {
+            context = context.with_shared_lists(shared_context);
+        }
+        let gl_window = context.build_windowed(request.builder, event_loop)?;
 
         Ok(TrackedWindowContainer {
-            window,
+            handle,
+            parent: request.parent,
+            window: request.window_state,
             gl_window: IndeterminateWindowedContext::NotCurrent(gl_window),
             egui: None,
+            close_pending: false,
+            repaint_deadline: None,
+            repaint,
+            mailbox: vec![],
+            creation_results: vec![],
+            last_frame: None,
+            redraw_pending: false,
+            shared_textures_registered: shared_context.map(|_| 0),
+            retired_painter: None,
+            key: request.key,
+            restored_geometry: None,
+            #[cfg(feature = "persistence")]
+            pending_egui_memory: None,
+            style: None,
+            span,
+        })
+    }
+
+    /// Creates a window for a headless `MultiWindow`, which needs no display.
+    pub(crate) fn create_headless(
+        handle: WindowHandle,
+        request: NewWindowRequest<W>,
+        repaint: RepaintHandle,
+        shares_textures: bool,
+    ) -> Result<TrackedWindowContainer<W, M>, DisplayCreationError> {
+        // Checked all the same, so tests see the errors a window with a display would get.
+        request.gl_config.check()?;
+        Ok(TrackedWindowContainer {
+            handle,
+            parent: request.parent,
+            window: request.window_state,
+            gl_window: IndeterminateWindowedContext::Headless(HeadlessWindow::new()),
+            egui: None,
+            close_pending: false,
+            repaint_deadline: None,
+            repaint,
+            mailbox: vec![],
+            creation_results: vec![],
+            last_frame: None,
+            redraw_pending: false,
+            shared_textures_registered: if shares_textures { Some(0) } else { None },
+            retired_painter: None,
+            key: request.key,
+            restored_geometry: None,
+            #[cfg(feature = "persistence")]
+            pending_egui_memory: None,
+            style: None,
+            span: window_span(handle, request.parent),
         })
     }
 
-    pub fn is_event_for_window(&self, event: &glutin::event::Event<()>) -> bool {
+    pub fn is_event_for_window<T>(&self, event: &glutin::event::Event<T>) -> bool {
         // Check if the window ID matches, if not then this window can pass on the event.
-        match (event, &self.gl_window) {
-            (
-                Event::WindowEvent {
-                    window_id: id,
-                    event: _,
-                    ..
-                },
-                IndeterminateWindowedContext::PossiblyCurrent(gl_window),
-            ) => gl_window.window().id() == *id,
-            (
-                Event::WindowEvent {
-                    window_id: id,
-                    event: _,
-                    ..
-                },
-                IndeterminateWindowedContext::NotCurrent(gl_window),
-            ) => gl_window.window().id() == *id,
-            _ => true, // we weren't able to check the window ID, maybe this window is not initialized yet. we should run it.
-        }
-    }
-
-    pub fn handle_event_outer<T>(
-        &mut self,
-        event: &glutin::event::Event<()>,
-        el: &EventLoopWindowTarget<T>,
-        other_windows: Vec<&mut crate::windows::MyWindows>,
-    ) -> TrackedWindowControl {
-        // Activate this gl_window so we can use it.
-        // We cannot activate it without full ownership, so temporarily move the gl_window into the current scope.
-        // It *must* be returned at the end.
-        let gl_window = mem::replace(&mut self.gl_window, IndeterminateWindowedContext::None);
-        let mut gl_window = match gl_window {
-            IndeterminateWindowedContext::PossiblyCurrent(w) => unsafe {
-                w.make_current().unwrap()
-            },
-            IndeterminateWindowedContext::NotCurrent(w) => unsafe { w.make_current().unwrap() },
-            IndeterminateWindowedContext::None => panic!("there's no window context???"),
+        let id = match event {
+            Event::WindowEvent { window_id, .. } | Event::RedrawRequested(window_id) => window_id,
+            _ => return true, // not addressed to any particular window, so every window gets it.
         };
+        match &self.gl_window {
+            // Headless windows have no ID, so every redraw is run by all of them.
+            IndeterminateWindowedContext::Headless(_) => true,
+            gl_window => match gl_window.window() {
+                Some(window) => window.id() == *id,
+                None => true, // we weren't able to check the window ID, maybe this window is not initialized yet. we should run it.
+            },
+        }
+    }
+
+    /// Handles an event for the window. Messages and the like are handed to the window before the
+    /// event, so if the event then fails, the error comes with what the window asked for while
+    /// receiving them, which still has to be carried out.
+    pub fn handle_event_outer<T, TE, S>(
+        &mut self,
+        event: &glutin::event::Event<T>,
+        el: &EventLoopWindowTarget<TE>,
+        app: &mut AppCtx<W, S>,
+    ) -> Result<TrackedWindowControl<W, M>, (WindowError, TrackedWindowControl<W, M>)>
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let mut gl_window = self
+            .take_current()
+            .map_err(|e| (e, TrackedWindowControl::default()))?;
+
+        if let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } = event
+        {
+            self.request_close(app);
+        }
+
+        // Keyed windows keep track of where they are while they aren't maximized.
+        let geometry_changed = matches!(
+            event,
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_) | WindowEvent::Moved(_),
+                ..
+            }
+        );
+        if self.key.is_some() && (geometry_changed || self.restored_geometry.is_none()) {
+            let window = gl_window.window();
+            if !window.is_maximized() {
+                self.restored_geometry = Some(WindowGeometry::of(window));
+            }
+        }
+
+        // Messages, window creation outcomes and user events are handed over first, so the event
+        // sees their effects.
+        let message_control = self.open_mailbox(event, app);
 
         // Now that the window is active, create a context if it is missing.
-        match self.egui.as_ref() {
-            None => {
-                let gl = Arc::new(unsafe {
-                    glow::Context::from_loader_function(|s| gl_window.get_proc_address(s))
-                });
-
-                unsafe {
-                    use glow::HasContext as _;
-                    gl.enable(glow::FRAMEBUFFER_SRGB);
+        let repaint = self.repaint.clone();
+        #[cfg(feature = "persistence")]
+        let pending_egui_memory = &mut self.pending_egui_memory;
+        let style = &self.style;
+        let egui = self.egui.get_or_insert_with(|| {
+            let gl = Arc::new(unsafe {
+                glow::Context::from_loader_function(|s| gl_window.get_proc_address(s))
+            });
+
+            unsafe {
+                use glow::HasContext as _;
+                gl.enable(glow::FRAMEBUFFER_SRGB);
+            }
+
+            let egui = egui_glow::EguiGlow::new(el, gl);
+            egui.egui_ctx
+                .set_request_repaint_callback(move || repaint.request_repaint());
+            #[cfg(feature = "persistence")]
+            if let Some(memory) = pending_egui_memory.take() {
+                *egui.egui_ctx.memory() = memory;
+            }
+            if let Some(style) = style {
+                style.apply(&egui.egui_ctx);
+            }
+
+            // Nothing has been drawn yet.
+            gl_window.window().request_redraw();
+            egui
+        });
+
+        if let Some(style) = &self.style {
+            // Set before every event, as egui goes back to the monitor's scale factor whenever it
+            // changes.
+            let pixels_per_point = style
+                .pixels_per_point
+                .unwrap_or_else(|| gl_window.window().scale_factor() as f32);
+            egui.egui_winit.set_pixels_per_point(pixels_per_point);
+        }
+
+        // Windows painting in `handle_event` can draw the shared textures loaded before the event.
+        if let (Some(registered), Some(shared_textures)) = (
+            &mut self.shared_textures_registered,
+            app.shared_textures.as_deref_mut(),
+        ) {
+            shared_textures.register(&mut egui.painter, registered);
+        }
+
+        let result = match self.window.handle_event(event, app, egui, &mut gl_window) {
+            Ok(Some(control)) => Ok(control),
+            Ok(None) => default_frame_loop(
+                &mut self.window,
+                event,
+                app,
+                egui,
+                &mut gl_window,
+                &mut self.repaint_deadline,
+                &self.repaint.requested,
+                &mut self.last_frame,
+                &mut self.redraw_pending,
+                &mut self.shared_textures_registered,
+            ),
+            Err(e) => Err(e),
+        };
+
+        // The context is given back even if the event couldn't be handled, so the window can try
+        // again.
+        self.gl_window = IndeterminateWindowedContext::PossiblyCurrent(gl_window);
+        let mut result = match result {
+            Ok(result) => result,
+            Err(e) => return Err((e, message_control)),
+        };
+        result.merge(message_control);
+        // A window asking to exit is closed by the `MultiWindow`, which remembers its geometry and
+        // egui memory before destroying it.
+        Ok(result)
+    }
+
+    /// Handles an event for a window of a headless `MultiWindow`. Redraws run a frame, everything
+    /// else only delivers the window's messages and user events. A close requested through
+    /// `HeadlessWindow::request_close` is answered first.
+    pub(crate) fn handle_headless_event<T, S>(
+        &mut self,
+        event: &glutin::event::Event<T>,
+        app: &mut AppCtx<W, S>,
+    ) -> TrackedWindowControl<W, M>
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let close_requested = match &mut self.gl_window {
+            IndeterminateWindowedContext::Headless(headless) => headless.take_close_request(),
+            _ => false,
+        };
+        if close_requested {
+            self.request_close(app);
+        }
+        let message_control = self.open_mailbox(event, app);
+        let mut result = match (&mut self.gl_window, event) {
+            (IndeterminateWindowedContext::Headless(headless), Event::RedrawRequested(_)) => {
+                self.repaint.requested.store(false, Ordering::SeqCst);
+                let _frame = tracing::debug_span!("frame").entered();
+                let window = &mut self.window;
+                let control = headless.run_frame(|ctx| window.update(ctx, app));
+                if let (Some(registered), Some(shared_textures)) = (
+                    &mut self.shared_textures_registered,
+                    app.shared_textures.as_deref(),
+                ) {
+                    shared_textures.register_headless(headless, registered);
                 }
+                control
+            }
+            (IndeterminateWindowedContext::Headless(_), _) => TrackedWindowControl::default(),
+            _ => panic!("only headless windows can handle headless events"),
+        };
+        result.merge(message_control);
+        result
+    }
+
+    /// Hands every window creation outcome and message waiting for the window to it, and the
+    /// event if it is a user event.
+    fn open_mailbox<T, S>(
+        &mut self,
+        event: &glutin::event::Event<T>,
+        app: &mut AppCtx<W, S>,
+    ) -> TrackedWindowControl<W, M>
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let mut control = TrackedWindowControl::default();
+        for result in mem::take(&mut self.creation_results) {
+            control.merge(self.window.on_window_created(result, app));
+        }
+        for message in mem::take(&mut self.mailbox) {
+            control.merge(self.window.on_message(message, app));
+        }
+        if let Event::UserEvent(event) = event {
+            control.merge(self.window.on_user_event(event, app));
+        }
+        control
+    }
 
-                let egui = egui_glow::EguiGlow::new(&el, gl.clone());
-                self.egui = Some(egui);
+    /// Asks the window whether it may close, as the user wants it to.
+    fn request_close<T, S>(&mut self, app: &mut AppCtx<W, S>)
+    where
+        W: TrackedWindow<W, T, S, M>,
+    {
+        let response = self.window.on_close_requested(app);
+        tracing::debug!(?response, "close requested");
+        match response {
+            CloseRequestResponse::Allow => app.close(self.handle),
+            CloseRequestResponse::Deny => (),
+            CloseRequestResponse::Defer => self.close_pending = true,
+        }
+    }
+
+    /// The window's egui context, unless it hasn't handled an event yet.
+    pub(crate) fn egui_ctx(&self) -> Option<&egui::Context> {
+        match (&self.gl_window, &self.egui) {
+            (IndeterminateWindowedContext::Headless(headless), _) => Some(headless.egui_ctx()),
+            (_, Some(egui)) => Some(&egui.egui_ctx),
+            _ => None,
+        }
+    }
+
+    /// Replaces the window's egui memory with a remembered one. A window without an egui context
+    /// yet gets it as soon as the context is created.
+    #[cfg(feature = "persistence")]
+    pub(crate) fn restore_egui_memory(&mut self, memory: egui::Memory) {
+        match self.egui_ctx() {
+            Some(ctx) => *ctx.memory() = memory,
+            None => self.pending_egui_memory = Some(memory),
+        }
+    }
+
+    /// Gives the window the `MultiWindow`'s style. A window without an egui context yet gets it as
+    /// soon as the context is created.
+    pub(crate) fn set_style(&mut self, style: Arc<AppStyle>) {
+        if let Some(ctx) = self.egui_ctx() {
+            style.apply(ctx);
+        }
+        if let (IndeterminateWindowedContext::Headless(headless), Some(pixels_per_point)) =
+            (&mut self.gl_window, style.pixels_per_point)
+        {
+            headless.set_pixels_per_point(pixels_per_point);
+        }
+        self.style = Some(style);
+        self.repaint.request_repaint();
+    }
+
+    /// Requests a redraw if a repaint was asked for since the window's last frame.
+    pub(crate) fn redraw_if_requested(&mut self) {
+        if self.repaint.requested.swap(false, Ordering::SeqCst) {
+            if let Some(window) = self.gl_window.window() {
+                window.request_redraw();
             }
-            Some(_) => (),
-            _ => {
-                panic!("Partially initialized window");
+        }
+    }
+
+    /// Requests a redraw if this window's delayed repaint is due by `now`.
+    pub(crate) fn redraw_if_due(&mut self, now: Instant) {
+        if let Some(deadline) = self.repaint_deadline {
+            if deadline <= now {
+                self.repaint_deadline = None;
+                if let Some(window) = self.gl_window.window() {
+                    window.request_redraw();
+                }
             }
+        }
+    }
+
+    /// Captures what the window showed in its last frame, by painting that frame again and reading
+    /// it back from the framebuffer. Only works for windows painted by the default frame loop.
+    /// Headless windows render the frame in software instead, see `HeadlessWindow::screenshot`.
+    pub fn screenshot(&mut self) -> Result<image::RgbaImage, ScreenshotError> {
+        if let IndeterminateWindowedContext::Headless(headless) = &self.gl_window {
+            return headless.screenshot();
+        }
+        if self.egui.is_none() || self.last_frame.is_none() {
+            return Err(ScreenshotError::NoFrame);
+        }
+        let gl_window = self.take_current()?;
+        let (egui, last_frame) = match (self.egui.as_mut(), self.last_frame.as_ref()) {
+            (Some(egui), Some(last_frame)) => (egui, last_frame),
+            _ => unreachable!("both were checked above"),
         };
 
-        let result = match self.egui.as_mut() {
-            Some(egui) => {
-                let result = self
-                    .window
-                    .handle_event(event, other_windows, egui, &mut gl_window);
-                if let ControlFlow::Exit = result.requested_control_flow {
-                    // This window wants to go away. Close it.
+        let size = gl_window.window().inner_size();
+        paint(
+            egui,
+            size.into(),
+            last_frame.pixels_per_point,
+            &last_frame.primitives,
+            &Default::default(),
+        );
+        let mut pixels = vec![0; size.width as usize * size.height as usize * 4];
+        unsafe {
+            use glow::HasContext as _;
+            let gl = egui.painter.gl();
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+            gl.read_pixels(
+                0,
+                0,
+                size.width as i32,
+                size.height as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixels),
+            );
+        }
+        self.gl_window = IndeterminateWindowedContext::PossiblyCurrent(gl_window);
+
+        let image = image::RgbaImage::from_raw(size.width, size.height, pixels)
+            .expect("the buffer was sized for the image");
+        // GL's rows start at the bottom.
+        Ok(image::imageops::flip_vertical(&image))
+    }
+
+    /// Takes a `screenshot` and saves it, in the format given by the extension of `path`.
+    pub fn save_screenshot(&mut self, path: impl AsRef<Path>) -> Result<(), ScreenshotError> {
+        self.screenshot()?.save(path)?;
+        Ok(())
+    }
+
+    /// Releases this window's egui resources. They live in the window's GL context, so it is made
+    /// current first. Does nothing if they were already released.
+    pub fn destroy(&mut self) {
+        if let Some(mut egui) = self.egui.take() {
+            // If the context can't be made current, the resources can't be released either. They
+            // go away along with the context.
+            if let Ok(gl_window) = self.take_current() {
+                if self.draws_shared_textures() {
+                    // Destroying the painter would delete the shared textures along with its own,
+                    // so only the textures egui made for this window are freed. The painter's
+                    // shader program and buffers are destroyed once no open window draws the
+                    // shared textures.
+                    let allocated: Vec<_> = egui
+                        .egui_ctx
+                        .tex_manager()
+                        .read()
+                        .allocated()
+                        .map(|(id, _)| *id)
+                        .collect();
+                    for id in allocated {
+                        egui.painter.free_texture(id);
+                    }
+                    self.retired_painter = Some(egui.painter);
+                } else {
                     egui.destroy();
-                };
-                result
+                }
+                self.gl_window = IndeterminateWindowedContext::PossiblyCurrent(gl_window);
             }
-            _ => {
-                panic!("Window wasn't fully initialized");
+        }
+    }
+
+    /// Whether this window's painter knows any of the `SharedTextures`.
+    pub(crate) fn draws_shared_textures(&self) -> bool {
+        self.egui.is_some() && matches!(self.shared_textures_registered, Some(n) if n > 0)
+    }
+
+    /// Activates this gl_window so we can use it.
+    /// We cannot activate it without full ownership, so the gl_window is temporarily moved out of
+    /// the container. It *must* be returned when the caller is done with it. If it can't be
+    /// activated, it stays in the container.
+    fn take_current(&mut self) -> Result<glutin::WindowedContext<PossiblyCurrent>, WindowError> {
+        let gl_window = mem::replace(&mut self.gl_window, IndeterminateWindowedContext::None);
+        let (gl_window, e) = match gl_window {
+            IndeterminateWindowedContext::PossiblyCurrent(w) => match unsafe { w.make_current() } {
+                Ok(w) => return Ok(w),
+                Err((w, e)) => (IndeterminateWindowedContext::PossiblyCurrent(w), e),
+            },
+            IndeterminateWindowedContext::NotCurrent(w) => match unsafe { w.make_current() } {
+                Ok(w) => return Ok(w),
+                Err((w, e)) => (IndeterminateWindowedContext::NotCurrent(w), e),
+            },
+            IndeterminateWindowedContext::Headless(headless) => {
+                self.gl_window = IndeterminateWindowedContext::Headless(headless);
+                return Err(WindowError::Headless);
             }
+            IndeterminateWindowedContext::None => return Err(WindowError::NoContext),
         };
+        self.gl_window = gl_window;
+        Err(WindowError::Context(e))
+    }
+}
+
+/// The span a window's events are handled in.
+fn window_span(handle: WindowHandle, parent: Option<WindowHandle>) -> tracing::Span {
+    tracing::info_span!("window", handle = handle.0, parent = ?parent.map(|parent| parent.0))
+}
+
+/// The color behind every window's UI.
+pub(crate) fn background_color() -> egui::Rgba {
+    egui::Rgba::from_rgb(0.1, 0.3, 0.2)
+}
 
-        match mem::replace(
-            &mut self.gl_window,
-            IndeterminateWindowedContext::PossiblyCurrent(gl_window),
+/// Clears the back buffer to the window's background color and paints egui's output on top.
+fn paint(
+    egui: &mut EguiGlow,
+    screen_size_px: [u32; 2],
+    pixels_per_point: f32,
+    primitives: &[egui::ClippedPrimitive],
+    textures_delta: &egui::TexturesDelta,
+) {
+    let color = background_color();
+    unsafe {
+        use glow::HasContext as _;
+        egui.painter
+            .gl()
+            .clear_color(color[0], color[1], color[2], color[3]);
+        egui.painter.gl().clear(glow::COLOR_BUFFER_BIT);
+    }
+
+    egui.painter.paint_and_update_textures(
+        screen_size_px,
+        pixels_per_point,
+        primitives,
+        textures_delta,
+    );
+}
+
+/// Handles an event for a window that leaves the work to `TrackedWindow::update`: feeds window
+/// events to egui, and on redraws runs a frame, paints it and schedules the next one.
+#[allow(clippy::too_many_arguments)]
+fn default_frame_loop<W: TrackedWindow<W, T, S, M>, T, S, M>(
+    window: &mut W,
+    event: &glutin::event::Event<T>,
+    app: &mut AppCtx<W, S>,
+    egui: &mut EguiGlow,
+    gl_window: &mut glutin::WindowedContext<PossiblyCurrent>,
+    repaint_deadline: &mut Option<Instant>,
+    repaint_requested: &AtomicBool,
+    last_frame: &mut Option<LastFrame>,
+    redraw_pending: &mut bool,
+    shared_textures_registered: &mut Option<usize>,
+) -> Result<TrackedWindowControl<W, M>, WindowError> {
+    let mut redraw = || -> Result<TrackedWindowControl<W, M>, WindowError> {
+        // This frame is the repaint that was asked for. Requests made while running it show up in
+        // `repaint_after` instead.
+        repaint_requested.store(false, Ordering::SeqCst);
+        let _frame = tracing::debug_span!("frame").entered();
+
+        let input = egui.egui_winit.take_egui_input(gl_window.window());
+        let ppp = input.pixels_per_point;
+        egui.egui_ctx.begin_frame(input);
+
+        let mut control = window.update(&egui.egui_ctx, app);
+
+        let full_output = egui.egui_ctx.end_frame();
+        egui.egui_winit.handle_platform_output(
+            gl_window.window(),
+            &egui.egui_ctx,
+            full_output.platform_output,
+        );
+
+        // A window asking to be closed keeps that request, otherwise egui decides when the next
+        // frame is needed. A delayed repaint is remembered so the `MultiWindow` can redraw this
+        // window once it is due; `repaint_after` is `Duration::MAX` when egui doesn't need one.
+        *repaint_deadline = None;
+        if let ControlFlow::ExitWithCode(_) = control.requested_control_flow {
+        } else if full_output.repaint_after.is_zero() {
+            gl_window.window().request_redraw();
+            control.requested_control_flow = ControlFlow::Poll;
+        } else if let Some(deadline) = Instant::now().checked_add(full_output.repaint_after) {
+            *repaint_deadline = Some(deadline);
+            control.requested_control_flow = ControlFlow::WaitUntil(deadline);
+        } else {
+            control.requested_control_flow = ControlFlow::Wait;
+        }
+
+        // The frame may use shared textures loaded while it was built.
+        if let (Some(registered), Some(shared_textures)) = (
+            shared_textures_registered.as_mut(),
+            app.shared_textures.as_deref_mut(),
         ) {
-            IndeterminateWindowedContext::None => (),
-            _ => {
-                panic!("Window had a GL context while we were borrowing it?");
+            shared_textures.register(&mut egui.painter, registered);
+        }
+
+        let prim = egui.egui_ctx.tessellate(full_output.shapes);
+        let pixels_per_point = ppp.unwrap_or(1.0);
+        paint(
+            egui,
+            gl_window.window().inner_size().into(),
+            pixels_per_point,
+            &prim,
+            &full_output.textures_delta,
+        );
+        *last_frame = Some(LastFrame {
+            primitives: prim,
+            pixels_per_point,
+        });
+
+        gl_window.swap_buffers()?;
+        Ok(control)
+    };
+
+    let control = match event {
+        // Platform-dependent event handlers to workaround a winit bug
+        // See: https://github.com/rust-windowing/winit/issues/987
+        // See: https://github.com/rust-windowing/winit/issues/1619
+        // `RedrawEventsCleared` isn't addressed to any window, so each window only runs a frame
+        // on it if it was asked to redraw since its last one.
+        Event::RedrawRequested(_) if cfg!(windows) => {
+            *redraw_pending = true;
+            TrackedWindowControl::default()
+        }
+        Event::RedrawEventsCleared if cfg!(windows) && mem::take(redraw_pending) => redraw()?,
+        Event::RedrawRequested(_) if !cfg!(windows) => redraw()?,
+
+        Event::WindowEvent { event, .. } => {
+            if let WindowEvent::Resized(physical_size) = event {
+                gl_window.resize(*physical_size);
             }
+
+            let consumed = egui.on_event(event);
+            if consumed || window_event_needs_repaint(egui, event) {
+                gl_window.window().request_redraw();
+            }
+            TrackedWindowControl::default()
         }
-        result
 
-        // self.gl_window.makecurr
-        // We have to take ownership of it, because make_current()
+        _ => TrackedWindowControl::default(),
+    };
+
+    // Something asked egui for a repaint outside of a frame, e.g. from `handle_event`.
+    if repaint_requested.swap(false, Ordering::SeqCst) {
+        gl_window.window().request_redraw();
+    }
+    Ok(control)
+}
 
-        // let gl_window = mem::replace(&mut self.egui_window.gl_window, WindowedContext::None);
-        // let gl_window = match gl_window {
-        //     WindowedContext::PossiblyCurrent(w) => unsafe {w.make_current().unwrap()},
-        //     WindowedContext::NotCurrent(w) => unsafe {w.make_current().unwrap()},
-        //     WindowedContext::None => panic!("there's no window context???"),
-        // };
+/// Whether a window event that egui didn't consume can still change what the window shows. Events
+/// egui ignores don't, and neither does the pointer moving over parts of the window without any
+/// egui area under it, which egui has no reason to react to.
+fn window_event_needs_repaint(egui: &EguiGlow, event: &WindowEvent) -> bool {
+    match event {
+        WindowEvent::CursorMoved { position, .. } => {
+            let pixels_per_point = egui.egui_winit.pixels_per_point();
+            let pos = egui::pos2(
+                position.x as f32 / pixels_per_point,
+                position.y as f32 / pixels_per_point,
+            );
+            // Checking where the pointer was last frame catches it leaving an area.
+            egui.egui_ctx.is_using_pointer()
+                || egui.egui_ctx.is_pointer_over_area()
+                || egui.egui_ctx.layer_id_at(pos).is_some()
+        }
+        WindowEvent::Occluded(occluded) => !occluded,
+        WindowEvent::Moved(_)
+        | WindowEvent::Destroyed
+        | WindowEvent::Focused(_)
+        | WindowEvent::ModifiersChanged(_)
+        | WindowEvent::CursorEntered { .. }
+        | WindowEvent::TouchpadPressure { .. }
+        | WindowEvent::AxisMotion { .. }
+        | WindowEvent::ThemeChanged(_) => false,
+        _ => true,
     }
 }
 
 pub enum IndeterminateWindowedContext {
     PossiblyCurrent(glutin::WindowedContext<glutin::PossiblyCurrent>),
     NotCurrent(glutin::WindowedContext<glutin::NotCurrent>),
+    /// The window belongs to a headless `MultiWindow`, so it has neither a window nor a context.
+    Headless(HeadlessWindow),
     None,
 }
 
-pub struct TrackedWindowControl {
+impl IndeterminateWindowedContext {
+    /// The winit window, unless the context is currently borrowed.
+    pub fn window(&self) -> Option<&glutin::window::Window> {
+        match self {
+            IndeterminateWindowedContext::PossiblyCurrent(w) => Some(w.window()),
+            IndeterminateWindowedContext::NotCurrent(w) => Some(w.window()),
+            IndeterminateWindowedContext::Headless(_) | IndeterminateWindowedContext::None => None,
+        }
+    }
+}
+
+pub struct TrackedWindowControl<W, M = ()> {
+    /// `ControlFlow::Exit` closes this window and its children. `ExitWithCode` with any other code
+    /// closes every window and exits the process with that code, as `AppCtx::exit` does.
     pub requested_control_flow: ControlFlow,
-    pub windows_to_create: Vec<NewWindowRequest>,
+    pub windows_to_create: Vec<NewWindowRequest<W>>,
+    /// Answers to deferred close requests, keyed by the handle of the window that deferred.
+    /// Answers for windows without a pending close request are ignored.
+    pub close_responses: Vec<(WindowHandle, CloseRequestResponse)>,
+    /// Messages to deliver once the current event has been handled. Each recipient is woken up
+    /// for a repaint and receives the message through `TrackedWindow::on_message`.
+    pub messages: Vec<(Recipient<W>, M)>,
+}
+
+impl<W, M> TrackedWindowControl<W, M> {
+    /// Adds the requests of `other` to this one. Only an exit request from `other` overrides this
+    /// control flow.
+    pub(crate) fn merge(&mut self, mut other: TrackedWindowControl<W, M>) {
+        if let ControlFlow::ExitWithCode(_) = other.requested_control_flow {
+            self.requested_control_flow = other.requested_control_flow;
+        }
+        self.windows_to_create.append(&mut other.windows_to_create);
+        self.close_responses.append(&mut other.close_responses);
+        self.messages.append(&mut other.messages);
+    }
+}
+
+impl<W, M> Default for TrackedWindowControl<W, M> {
+    fn default() -> Self {
+        TrackedWindowControl {
+            requested_control_flow: ControlFlow::Wait,
+            windows_to_create: vec![],
+            close_responses: vec![],
+            messages: vec![],
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -180,4 +910,30 @@ pub enum DisplayCreationError {
     Creation(#[from] glutin::CreationError),
     #[error("couldn't create context {0:?}")]
     Context(#[from] glutin::ContextError),
+    #[error("multisampling needs a power of two samples per pixel, not {0}")]
+    Multisampling(u16),
+}
+
+/// Why a window couldn't handle an event. Passed to `MultiWindow::on_error`, which decides what
+/// happens to the window.
+#[derive(Error, Debug)]
+pub enum WindowError {
+    #[error("GL context error {0}")]
+    Context(#[from] glutin::ContextError),
+    #[error("the window has no GL context")]
+    NoContext,
+    #[error("headless windows don't have a GL context")]
+    Headless,
+}
+
+#[derive(Error, Debug)]
+pub enum ScreenshotError {
+    #[error("there is no window {0:?}")]
+    UnknownWindow(WindowHandle),
+    #[error("the window hasn't painted a frame yet")]
+    NoFrame,
+    #[error("couldn't paint the window {0}")]
+    Window(#[from] WindowError),
+    #[error("couldn't save screenshot {0}")]
+    Save(#[from] image::ImageError),
 }
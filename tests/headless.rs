@@ -0,0 +1,232 @@
+//! Drives the example's windows without a display.
+
+#[allow(dead_code)]
+#[path = "../examples/multiwin/main.rs"]
+mod multiwin;
+
+// The example's windows find these at the root of the crate.
+use multiwin::{windows, AppEvent, AppMessage, AppState};
+
+use egui_multiwin::{
+    app_style::AppStyle, assert_window_snapshot, multi_window::MultiWindow,
+    tracked_window::WindowHandle,
+};
+use windows::{popup_window::PopupWindow, root::RootWindow, MyWindows};
+
+type App = MultiWindow<MyWindows, AppEvent, AppState, AppMessage>;
+
+/// A root window and a popup it owns, after their first frame.
+fn root_and_popup() -> (App, WindowHandle, WindowHandle) {
+    let mut multi_window = App::headless();
+    let root = multi_window
+        .add(RootWindow::request())
+        .expect("headless windows need no display");
+    let popup = multi_window
+        .add(PopupWindow::request("popup".to_string(), root))
+        .expect("headless windows need no display");
+    assert_eq!(multi_window.step(), None);
+    (multi_window, root, popup)
+}
+
+/// Clicks the text the window showed in its last frame.
+fn click(multi_window: &mut App, handle: WindowHandle, text: &str) {
+    let window = multi_window
+        .headless_window_mut(handle)
+        .expect("the window is open");
+    let rect = window
+        .find_text(text)
+        .unwrap_or_else(|| panic!("the window doesn't show {:?}", text));
+    window.click(rect.center());
+}
+
+fn popup_input(multi_window: &App, popup: WindowHandle) -> &str {
+    match multi_window.get(popup) {
+        Some(MyWindows::Popup(popup)) => &popup.input,
+        _ => panic!("the popup is closed"),
+    }
+}
+
+#[test]
+fn closing_the_root_closes_its_popup() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    assert!(multi_window.close(root));
+    assert!(multi_window.get(root).is_none());
+    assert!(multi_window.get(popup).is_none());
+}
+
+#[test]
+fn closing_a_popup_keeps_the_root() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    click(&mut multi_window, popup, "Quit");
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(popup).is_none());
+    assert!(multi_window.get(root).is_some());
+}
+
+#[test]
+fn reset_popups_clears_their_input() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    assert_eq!(popup_input(&multi_window, popup), "popup");
+    click(&mut multi_window, root, "Reset popups");
+    assert_eq!(multi_window.step_frames(2), None);
+    assert_eq!(popup_input(&multi_window, popup), "");
+}
+
+#[test]
+fn quit_in_the_root_exits() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    click(&mut multi_window, root, "Quit");
+    assert_eq!(multi_window.step(), Some(0));
+    assert!(multi_window.get(root).is_none());
+    assert!(multi_window.get(popup).is_none());
+}
+
+#[test]
+fn clicking_increment_in_a_popup_increases_the_roots_counter() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    click(&mut multi_window, popup, "Increment");
+    assert_eq!(multi_window.step(), None);
+    assert_eq!(multi_window.state().button_press_count, 1);
+    // The root shows the count from its next frame on.
+    assert_eq!(multi_window.step(), None);
+    let root_window = multi_window.headless_window_mut(root).unwrap();
+    assert!(root_window.find_text("number 1").is_some());
+}
+
+#[test]
+fn submitting_a_popup_shows_its_text_in_the_root() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    click(&mut multi_window, popup, "popup");
+    assert_eq!(multi_window.step(), None);
+    let popup_window = multi_window.headless_window_mut(popup).unwrap();
+    popup_window.press_key(egui::Key::End);
+    popup_window.type_text(" typed");
+    popup_window.press_key(egui::Key::Enter);
+    assert_eq!(multi_window.step_frames(2), None);
+    assert_eq!(popup_input(&multi_window, popup), "popup typed");
+    let root_window = multi_window.headless_window_mut(root).unwrap();
+    assert!(root_window.find_text("submitted: popup typed").is_some());
+}
+
+#[test]
+fn popup_looks_like_its_snapshot() {
+    let (mut multi_window, _, popup) = root_and_popup();
+    multi_window
+        .headless_window_mut(popup)
+        .unwrap()
+        .set_size(egui::vec2(400.0, 200.0));
+    assert_eq!(multi_window.step(), None);
+    assert_window_snapshot!(multi_window, popup, "popup");
+}
+
+#[test]
+fn quitting_saves_the_open_windows_for_the_next_run() {
+    let path = std::env::temp_dir().join(format!(
+        "egui-multiwin-test-session-{}.ron",
+        std::process::id()
+    ));
+    let (mut multi_window, root, _) = root_and_popup();
+    multi_window.remember_session(path.clone());
+    click(&mut multi_window, root, "Quit");
+    assert_eq!(multi_window.step(), Some(0));
+    // Nothing is left to save after the exit, and the saved session is kept.
+    assert_eq!(multi_window.step(), Some(0));
+
+    let mut next_run = App::headless();
+    let reopened = next_run.restore_session(&path);
+    std::fs::remove_file(&path).unwrap();
+    let reopened = reopened.expect("the session was saved");
+    assert_eq!(reopened.len(), 2);
+    let popup = reopened
+        .into_iter()
+        .find(|handle| matches!(next_run.get(*handle), Some(MyWindows::Popup(_))))
+        .expect("the popup was reopened");
+    assert_eq!(popup_input(&next_run, popup), "popup");
+}
+
+#[test]
+fn light_mode_keeps_the_rest_of_the_style() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    multi_window.set_style(AppStyle {
+        pixels_per_point: Some(1.5),
+        ..Default::default()
+    });
+    assert_eq!(multi_window.step(), None);
+    click(&mut multi_window, root, "Light mode");
+    assert_eq!(multi_window.step_frames(2), None);
+    let style = multi_window.style().expect("the style was set");
+    assert_eq!(style.pixels_per_point, Some(1.5));
+    let popup_window = multi_window.headless_window_mut(popup).unwrap();
+    assert!(!popup_window.egui_ctx().style().visuals.dark_mode);
+    assert_eq!(popup_window.pixels_per_point(), 1.5);
+}
+
+#[test]
+fn ticks_sent_through_a_proxy_reach_the_root() {
+    let (mut multi_window, root, _) = root_and_popup();
+    let proxy = multi_window.create_proxy();
+    proxy.send_event(AppEvent::Tick).unwrap();
+    proxy.send_event(AppEvent::Tick).unwrap();
+    assert_eq!(multi_window.step(), None);
+    match multi_window.get(root) {
+        Some(MyWindows::Root(root)) => assert_eq!(root.seconds_running, 2),
+        _ => panic!("the root is closed"),
+    }
+}
+
+#[test]
+fn closing_the_root_asks_first_while_popups_are_open() {
+    let (mut multi_window, root, popup) = root_and_popup();
+    multi_window
+        .headless_window_mut(root)
+        .unwrap()
+        .request_close();
+    assert_eq!(multi_window.step(), None);
+    click(&mut multi_window, root, "Cancel");
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(root).is_some());
+
+    multi_window
+        .headless_window_mut(root)
+        .unwrap()
+        .request_close();
+    assert_eq!(multi_window.step(), None);
+    click(&mut multi_window, root, "Close");
+    // The last window is gone, so the application exits.
+    assert_eq!(multi_window.step(), Some(0));
+    assert!(multi_window.get(root).is_none());
+    assert!(multi_window.get(popup).is_none());
+}
+
+#[test]
+fn closing_the_root_alone_needs_no_confirmation() {
+    let mut multi_window = App::headless();
+    let root = multi_window.add(RootWindow::request()).unwrap();
+    multi_window
+        .headless_window_mut(root)
+        .unwrap()
+        .request_close();
+    assert_eq!(multi_window.step(), Some(0));
+}
+
+#[cfg(feature = "persistence")]
+#[test]
+fn a_popup_closing_itself_has_its_egui_memory_remembered() {
+    let (mut multi_window, _, popup) = root_and_popup();
+    let id = egui::Id::new("remembered");
+    multi_window
+        .headless_window_mut(popup)
+        .unwrap()
+        .egui_ctx()
+        .data()
+        .insert_persisted(id, 42u32);
+    click(&mut multi_window, popup, "Quit");
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(popup).is_none());
+    let mut memory = multi_window
+        .egui_memory()
+        .get("popup/popup")
+        .expect("the popup's memory was remembered")
+        .clone();
+    assert_eq!(memory.data.get_persisted::<u32>(id), Some(42));
+}
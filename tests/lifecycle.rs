@@ -0,0 +1,232 @@
+//! When windows close and the application exits, driven by a window each test scripts.
+
+use egui_multiwin::{
+    app_ctx::AppCtx,
+    glutin::{event_loop::ControlFlow, window::WindowBuilder},
+    multi_window::{ExitPolicy, GlConfig, MultiWindow, NewWindowRequest},
+    tracked_window::{
+        CloseRequestResponse, DisplayCreationError, TrackedWindow, TrackedWindowControl,
+        WindowHandle,
+    },
+};
+
+/// Does nothing until a test gives it something to return from its next frame.
+struct Scripted {
+    next_frame: Option<TrackedWindowControl<Scripted>>,
+    /// How the window answers requests to close it.
+    close_response: CloseRequestResponse,
+    /// The outcomes of the windows it asked for.
+    created: Vec<Result<WindowHandle, DisplayCreationError>>,
+}
+
+impl Scripted {
+    fn request() -> NewWindowRequest<Scripted> {
+        let window = Scripted {
+            next_frame: None,
+            close_response: CloseRequestResponse::Allow,
+            created: vec![],
+        };
+        NewWindowRequest::new(window, WindowBuilder::new())
+    }
+}
+
+impl TrackedWindow<Scripted> for Scripted {
+    fn update(
+        &mut self,
+        _ctx: &egui::Context,
+        _app: &mut AppCtx<Scripted>,
+    ) -> TrackedWindowControl<Scripted> {
+        self.next_frame.take().unwrap_or_default()
+    }
+
+    fn on_window_created(
+        &mut self,
+        result: Result<WindowHandle, DisplayCreationError>,
+        _app: &mut AppCtx<Scripted>,
+    ) -> TrackedWindowControl<Scripted> {
+        self.created.push(result);
+        TrackedWindowControl::default()
+    }
+
+    fn on_close_requested(&mut self, _app: &mut AppCtx<Scripted>) -> CloseRequestResponse {
+        self.close_response
+    }
+}
+
+type App = MultiWindow<Scripted>;
+
+/// Two windows, after their first frame.
+fn two_windows() -> (App, WindowHandle, WindowHandle) {
+    let mut multi_window = App::headless();
+    let first = multi_window.add(Scripted::request()).unwrap();
+    let second = multi_window.add(Scripted::request()).unwrap();
+    assert_eq!(multi_window.step(), None);
+    (multi_window, first, second)
+}
+
+/// Has the window return `control` from its next frame.
+fn script(multi_window: &mut App, handle: WindowHandle, control: TrackedWindowControl<Scripted>) {
+    multi_window
+        .get_mut(handle)
+        .expect("the window is open")
+        .next_frame = Some(control);
+}
+
+/// Asks to close a window that defers, as the user clicking its close button would.
+fn defer_close(multi_window: &mut App, handle: WindowHandle) {
+    multi_window.get_mut(handle).unwrap().close_response = CloseRequestResponse::Defer;
+    multi_window
+        .headless_window_mut(handle)
+        .unwrap()
+        .request_close();
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(handle).is_some());
+}
+
+fn close_response(
+    handle: WindowHandle,
+    response: CloseRequestResponse,
+) -> TrackedWindowControl<Scripted> {
+    TrackedWindowControl {
+        close_responses: vec![(handle, response)],
+        ..Default::default()
+    }
+}
+
+fn control_flow(flow: ControlFlow) -> TrackedWindowControl<Scripted> {
+    TrackedWindowControl {
+        requested_control_flow: flow,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn closing_the_primary_window_exits_while_others_are_open() {
+    let (mut multi_window, primary, other) = two_windows();
+    multi_window.set_exit_policy(ExitPolicy::PrimaryWindowClosed(primary));
+    script(&mut multi_window, primary, control_flow(ControlFlow::Exit));
+    assert_eq!(multi_window.step(), Some(0));
+    assert!(multi_window.get(other).is_none());
+}
+
+#[test]
+fn closing_another_window_keeps_the_primary_window_running() {
+    let (mut multi_window, primary, other) = two_windows();
+    multi_window.set_exit_policy(ExitPolicy::PrimaryWindowClosed(primary));
+    script(&mut multi_window, other, control_flow(ControlFlow::Exit));
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(other).is_none());
+    assert!(multi_window.get(primary).is_some());
+}
+
+#[test]
+fn an_explicit_exit_policy_keeps_running_without_windows() {
+    let (mut multi_window, first, second) = two_windows();
+    multi_window.set_exit_policy(ExitPolicy::Explicit);
+    script(&mut multi_window, first, control_flow(ControlFlow::Exit));
+    script(&mut multi_window, second, control_flow(ControlFlow::Exit));
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(first).is_none());
+    assert!(multi_window.get(second).is_none());
+    assert_eq!(multi_window.step(), None);
+
+    multi_window.create_proxy().exit(3).unwrap();
+    assert_eq!(multi_window.step(), Some(3));
+}
+
+#[test]
+fn a_proxy_exits_with_windows_open() {
+    let (mut multi_window, first, second) = two_windows();
+    multi_window.create_proxy().exit(5).unwrap();
+    assert_eq!(multi_window.step(), Some(5));
+    assert!(multi_window.get(first).is_none());
+    assert!(multi_window.get(second).is_none());
+}
+
+#[test]
+fn exiting_with_a_code_closes_every_window_and_exits_with_it() {
+    let (mut multi_window, first, second) = two_windows();
+    multi_window.set_exit_policy(ExitPolicy::Explicit);
+    script(
+        &mut multi_window,
+        first,
+        control_flow(ControlFlow::ExitWithCode(7)),
+    );
+    assert_eq!(multi_window.step(), Some(7));
+    assert!(multi_window.get(first).is_none());
+    assert!(multi_window.get(second).is_none());
+    // The application stays exited.
+    assert_eq!(multi_window.step(), Some(7));
+}
+
+#[test]
+fn a_deferred_close_is_allowed_by_another_window() {
+    let (mut multi_window, window, dialog) = two_windows();
+    defer_close(&mut multi_window, window);
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(window).is_some());
+
+    script(
+        &mut multi_window,
+        dialog,
+        close_response(window, CloseRequestResponse::Allow),
+    );
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(window).is_none());
+    assert!(multi_window.get(dialog).is_some());
+}
+
+#[test]
+fn a_denied_close_keeps_the_window_until_it_is_asked_again() {
+    let (mut multi_window, window, dialog) = two_windows();
+    defer_close(&mut multi_window, window);
+    script(
+        &mut multi_window,
+        dialog,
+        close_response(window, CloseRequestResponse::Deny),
+    );
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(window).is_some());
+
+    // Nothing is pending any more, so an answer alone doesn't close the window.
+    script(
+        &mut multi_window,
+        dialog,
+        close_response(window, CloseRequestResponse::Allow),
+    );
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(window).is_some());
+
+    defer_close(&mut multi_window, window);
+    script(
+        &mut multi_window,
+        window,
+        close_response(window, CloseRequestResponse::Allow),
+    );
+    assert_eq!(multi_window.step(), None);
+    assert!(multi_window.get(window).is_none());
+}
+
+#[test]
+fn a_window_asking_for_bad_multisampling_is_told_why_it_wasnt_created() {
+    let (mut multi_window, window, _) = two_windows();
+    let gl_config = GlConfig {
+        multisampling: 3,
+        ..Default::default()
+    };
+    script(
+        &mut multi_window,
+        window,
+        TrackedWindowControl {
+            windows_to_create: vec![Scripted::request().with_gl_config(gl_config)],
+            ..Default::default()
+        },
+    );
+    // The outcome is handed over before the window's next frame.
+    assert_eq!(multi_window.step_frames(2), None);
+    let created = &multi_window.get(window).unwrap().created;
+    assert!(matches!(
+        created[..],
+        [Err(DisplayCreationError::Multisampling(3))]
+    ));
+}
@@ -0,0 +1,50 @@
+//! Reading and writing what is remembered about windows.
+
+use std::path::PathBuf;
+
+use egui_multiwin::persistence::{GeometryStore, WindowGeometry};
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "egui-multiwin-test-{}-{}.ron",
+        name,
+        std::process::id()
+    ))
+}
+
+#[test]
+fn geometry_is_read_back_as_it_was_saved() {
+    let path = temp_file("geometry");
+    let mut store = GeometryStore::default();
+    store.insert(
+        "root",
+        WindowGeometry {
+            position: Some((-20, 40)),
+            size: (800, 600),
+            maximized: true,
+            monitor: Some("DP-1".to_string()),
+        },
+    );
+    store.insert(
+        "popup/popup",
+        WindowGeometry {
+            position: None,
+            size: (320, 200),
+            maximized: false,
+            monitor: None,
+        },
+    );
+    store.save(&path).unwrap();
+    let loaded = GeometryStore::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), store);
+}
+
+#[test]
+fn a_missing_file_remembers_nothing() {
+    let path = temp_file("missing");
+    assert!(!path.exists());
+    let store = GeometryStore::load(&path).unwrap();
+    assert_eq!(store, GeometryStore::default());
+    assert!(store.get("root").is_none());
+}